@@ -0,0 +1,371 @@
+use crate::compare::aggregation::{FilePair, SplitSubmission};
+use crate::compare::compression::CompressionDistance;
+use crate::compare::context::CheckContext;
+use crate::compare::control::{CheckControl, Progress};
+use crate::compare::region::MatchRegion;
+use crate::compare::registry::ComparatorRegistry;
+use crate::models::base_code::BaseCode;
+use crate::models::comparison::AggregationMode;
+use crate::models::result::{CheckResult, DatabaseOrigin, SubmissionDigest};
+use crate::models::submission::Submission;
+use crate::models::verdict::Verdict;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use thiserror::Error;
+
+pub mod aggregation;
+pub mod ast;
+pub mod base_code;
+pub mod cache;
+pub mod cluster;
+pub mod compression;
+pub mod context;
+pub mod control;
+pub mod hash;
+pub mod language;
+pub mod lsh;
+pub mod normalize;
+pub mod region;
+pub mod registry;
+pub mod tiling;
+pub mod token;
+pub mod winnowing;
+
+/// Similarity algorithm.
+/// Implement it to add your own comparator, and register it in `ComparatorRegistry`.
+pub trait Comparator: Send + Sync {
+    /// Unique name of the comparator, used to select it in the database settings.
+    fn name(&self) -> &str;
+
+    /// Settings of the database are available through the context.
+    fn compare(
+        &self, first: &Submission, second: &Submission, context: &CheckContext,
+    ) -> Comparison;
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Comparison {
+    // Similarity in range 0.0..=1.0
+    pub similarity: f64,
+
+    pub regions: Vec<MatchRegion>,
+
+    // Best-matching file pairs, filled in the file aggregation mode
+    #[serde(default)]
+    pub files: Vec<FilePair>,
+
+    // Name of the comparator that scored the pair, filled by the check if left empty
+    #[serde(default)]
+    pub algorithm: String,
+}
+
+/// Raw match found by a comparator, resolved into `MatchRegion` against the compared files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Match {
+    pub first: Span,
+    pub second: Span,
+
+    // Size of the match in units of the comparator: tokens, or syntax nodes for AST
+    pub length: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    // Index of the file in the submission
+    pub file: usize,
+
+    // Byte offsets in the file content
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Submissions of another database (e.g. previous semester), compared read-only.
+#[derive(Debug, Clone)]
+pub struct Reference<'a> {
+    pub origin: DatabaseOrigin,
+    pub submissions: &'a [Submission],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairComparison {
+    // Indexes of the compared submissions
+    pub first: usize,
+    pub second: usize,
+
+    pub comparison: Comparison,
+}
+
+/// Comparison of two versions of the same submission.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionComparison {
+    // Version numbers, the first one is older
+    pub first: u32,
+    pub second: u32,
+
+    pub comparison: Comparison,
+}
+
+/// Compares every pair of submissions with the given comparator, in parallel.
+/// Fragments matching the base code and files below the minimum size are excluded before comparison.
+/// With LSH enabled, only candidate pairs are compared, the rest are left out of the result.
+pub fn compare_all(
+    submissions: &[Submission], base_code: &[BaseCode], comparator: &dyn Comparator,
+    context: &CheckContext, control: &CheckControl,
+) -> Result<Vec<PairComparison>, CompareError> {
+    let submissions: Vec<&Submission> = submissions.iter().collect();
+    let submissions = prepare(&submissions, base_code, context);
+    let pairs = candidate_pairs(&submissions, context);
+
+    compare_pairs(&submissions, pairs, comparator, context, control)
+}
+
+/// Runs a check with the comparator selected in the settings.
+/// Submissions of the references are compared only with the checked submissions.
+/// Pairs of submissions unchanged since the previous compatible result are not compared again.
+pub fn check(
+    submissions: &[Submission], base_code: &[BaseCode], references: &[Reference],
+    registry: &ComparatorRegistry, context: &CheckContext,
+    previous: Option<&CheckResult>, control: &CheckControl,
+) -> Result<CheckResult, CompareError> {
+    let comparator = registry.resolve(context.settings)?;
+
+    // Checked submissions go first, then the submissions of every reference
+    let mut origins: Vec<Option<DatabaseOrigin>> = vec![None; submissions.len()];
+    let mut all: Vec<&Submission> = submissions.iter().collect();
+    for reference in references {
+        origins.extend(std::iter::repeat_n(
+            Some(reference.origin.clone()),
+            reference.submissions.len(),
+        ));
+        all.extend(reference.submissions.iter());
+    }
+
+    let all = prepare(&all, base_code, context);
+    context.retain_cache(&all);
+
+    // Digests are taken after the exclusions, so changed base code invalidates them
+    let digests: Vec<SubmissionDigest> = all
+        .par_iter()
+        .zip(origins.into_par_iter())
+        .map(|(submission, origin)| SubmissionDigest::new(submission, origin))
+        .collect();
+    let previous = previous
+        .filter(|result| result.is_compatible(&context.settings.comparison))
+        .map(CheckResult::unchanged_pairs);
+
+    let mut reused = Vec::new();
+    let mut pairs = Vec::new();
+    for (first, second) in candidate_pairs(&all, context) {
+        // Pairs are ordered, so the first submission of a pair between references is a reference
+        if first >= submissions.len() {
+            continue;
+        }
+
+        let previous_pair = match (&previous, digests.get(first), digests.get(second)) {
+            (Some(previous), Some(first_digest), Some(second_digest)) => previous
+                .find(first_digest, second_digest)
+                // Discarded regions are needed again once the pair is flagged
+                .filter(|pair| {
+                    !pair.regions_discarded
+                        || context.settings.thresholds.classify(pair.similarity)
+                            == Verdict::Clear
+                })
+                // The same content may be resubmitted as another version
+                .map(|pair| pair.reused(first_digest, second_digest)),
+            _ => None,
+        };
+
+        match previous_pair {
+            Some(pair) => reused.push(pair),
+            None => pairs.push((first, second)),
+        }
+    }
+
+    let comparisons = compare_pairs(&all, pairs, comparator.as_ref(), context, control)?;
+
+    let mut result =
+        CheckResult::new(digests, comparisons, reused, &context.settings.comparison);
+    result.classify(context.settings);
+
+    Ok(result)
+}
+
+/// Compares every two versions of the submission with the comparator selected in the settings.
+/// LSH and the assignment scope are not applied, all version pairs are compared.
+pub fn compare_versions(
+    submission: &Submission, base_code: &[BaseCode], registry: &ComparatorRegistry,
+    context: &CheckContext, control: &CheckControl,
+) -> Result<Vec<VersionComparison>, CompareError> {
+    let comparator = registry.resolve(context.settings)?;
+
+    let versions = submission.versions();
+    let versions: Vec<&Submission> = versions.iter().collect();
+    let versions = prepare(&versions, base_code, context);
+    let pairs = (0..versions.len())
+        .flat_map(|first| (first + 1..versions.len()).map(move |second| (first, second)))
+        .collect();
+
+    let version_of =
+        |index: usize| versions.get(index).map_or(0, |version| version.version);
+    let comparisons =
+        compare_pairs(&versions, pairs, comparator.as_ref(), context, control)?
+            .into_iter()
+            .map(|pair| VersionComparison {
+                first: version_of(pair.first),
+                second: version_of(pair.second),
+                comparison: pair.comparison,
+            })
+            .collect();
+
+    Ok(comparisons)
+}
+
+/// Excludes the base code, then the files left too small to compare.
+fn prepare(
+    submissions: &[&Submission], base_code: &[BaseCode], context: &CheckContext,
+) -> Vec<Submission> {
+    let settings = &context.settings.comparison;
+
+    submissions
+        .par_iter()
+        .map(|submission| {
+            aggregation::remove_small_files(
+                base_code::exclude_base_code(submission, base_code, context.settings),
+                settings.aggregation.minimum_file_tokens,
+                &settings.normalization,
+            )
+        })
+        .collect()
+}
+
+/// LSH candidates, or every pair of submissions, within the assignment scope.
+fn candidate_pairs(
+    submissions: &[Submission], context: &CheckContext,
+) -> Vec<(usize, usize)> {
+    let pairs = if context.settings.comparison.lsh.is_enabled {
+        lsh::candidate_pairs(submissions, context)
+    } else {
+        (0..submissions.len())
+            .flat_map(|first| {
+                (first + 1..submissions.len()).map(move |second| (first, second))
+            })
+            .collect()
+    };
+
+    let scope = &context.settings.scope;
+    pairs
+        .into_iter()
+        .filter(|&(first, second)| {
+            match (submissions.get(first), submissions.get(second)) {
+                (Some(first), Some(second)) => {
+                    scope.allows(&first.metadata, &second.metadata)
+                },
+                _ => false,
+            }
+        })
+        .collect()
+}
+
+/// Selected comparator with the fallback for submissions made only of files without
+/// a tokenizer: their token rules are too coarse, so compression distance compares them.
+struct FallbackComparator<'a> {
+    comparator: &'a dyn Comparator,
+}
+
+impl Comparator for FallbackComparator<'_> {
+    fn name(&self) -> &str {
+        self.comparator.name()
+    }
+
+    fn compare(
+        &self, first: &Submission, second: &Submission, context: &CheckContext,
+    ) -> Comparison {
+        let comparator: &dyn Comparator =
+            if CompressionDistance::suits(first) || CompressionDistance::suits(second) {
+                context.compression()
+            } else {
+                self.comparator
+            };
+
+        let mut comparison = comparator.compare(first, second, context);
+        if comparison.algorithm.is_empty() {
+            comparison.algorithm = comparator.name().to_string();
+        }
+        comparison
+    }
+}
+
+fn compare_pairs(
+    submissions: &[Submission], pairs: Vec<(usize, usize)>, comparator: &dyn Comparator,
+    context: &CheckContext, control: &CheckControl,
+) -> Result<Vec<PairComparison>, CompareError> {
+    let comparator = FallbackComparator { comparator };
+    let total = pairs.len();
+    let completed = AtomicUsize::new(0);
+
+    let split: Vec<SplitSubmission> = match context.settings.comparison.aggregation.mode {
+        AggregationMode::File => submissions
+            .par_iter()
+            .map(|submission| {
+                SplitSubmission::new(
+                    submission,
+                    &context.settings.comparison.normalization,
+                )
+            })
+            .collect(),
+        AggregationMode::Submission => Vec::new(),
+    };
+
+    let comparisons: Option<Vec<PairComparison>> = pairs
+        .into_par_iter()
+        .map(|(first, second)| {
+            // Cancelled check stops at the next pair
+            if control.is_cancelled() {
+                return None;
+            }
+
+            let comparison = match context.settings.comparison.aggregation.mode {
+                AggregationMode::File => aggregation::compare_files(
+                    split.get(first)?,
+                    split.get(second)?,
+                    &comparator,
+                    context,
+                ),
+                AggregationMode::Submission => comparator.compare(
+                    submissions.get(first)?,
+                    submissions.get(second)?,
+                    context,
+                ),
+            };
+
+            let progress = Progress {
+                completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                total,
+            };
+            if progress.should_report() {
+                control.report(progress);
+            }
+
+            Some(PairComparison {
+                first,
+                second,
+                comparison,
+            })
+        })
+        .collect();
+
+    match comparisons {
+        Some(comparisons) if !control.is_cancelled() => Ok(comparisons),
+        _ => Err(CompareError::Cancelled),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CompareError {
+    #[error("Comparator \"{0}\" is not registered")]
+    UnknownComparator(String),
+
+    #[error("Check was cancelled")]
+    Cancelled,
+}
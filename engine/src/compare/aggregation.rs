@@ -0,0 +1,190 @@
+use crate::compare::context::CheckContext;
+use crate::compare::token::{TokenKind, tokenize_file};
+use crate::compare::{Comparator, Comparison};
+use crate::models::comparison::NormalizationSettings;
+use crate::models::submission::{CodeFile, Submission};
+use serde::{Deserialize, Serialize};
+
+/// Similarity of two files from the compared submissions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilePair {
+    // Relative paths of the files in their submissions
+    pub first: String,
+    pub second: String,
+
+    // Similarity in range 0.0..=1.0
+    pub similarity: f64,
+}
+
+/// Submission split into single-file submissions, ready for file-to-file comparison.
+#[derive(Debug, Clone)]
+pub(crate) struct SplitSubmission {
+    files: Vec<Submission>,
+
+    // Amount of tokens in every file, used as its weight
+    sizes: Vec<usize>,
+}
+
+/// Amount of code tokens in the file, comments are not counted.
+pub fn file_tokens(file: &CodeFile, normalization: &NormalizationSettings) -> usize {
+    tokenize_file(file, normalization)
+        .iter()
+        .filter(|token| token.kind != TokenKind::Comment)
+        .count()
+}
+
+/// Removes files with fewer tokens than the minimum from the submission.
+pub(crate) fn remove_small_files(
+    mut submission: Submission, minimum_tokens: usize,
+    normalization: &NormalizationSettings,
+) -> Submission {
+    if minimum_tokens > 0 {
+        submission
+            .files
+            .retain(|file| file_tokens(file, normalization) >= minimum_tokens);
+    }
+    submission
+}
+
+impl SplitSubmission {
+    pub(crate) fn new(
+        submission: &Submission, normalization: &NormalizationSettings,
+    ) -> Self {
+        Self {
+            files: submission
+                .files
+                .iter()
+                .map(|file| Submission {
+                    id: submission.id.clone(),
+                    metadata: submission.metadata.clone(),
+                    files: vec![file.clone()],
+                    provenance: None,
+                    version: submission.version,
+                    submitted_at: submission.submitted_at,
+                    history: Vec::new(),
+                })
+                .collect(),
+            sizes: submission
+                .files
+                .iter()
+                .map(|file| file_tokens(file, normalization))
+                .collect(),
+        }
+    }
+}
+
+/// Compares every file with every file of the other submission.
+/// Each file is scored by its best match, the score of the pair is
+/// the average of file scores of both submissions, weighted by file size.
+pub(crate) fn compare_files(
+    first: &SplitSubmission, second: &SplitSubmission, comparator: &dyn Comparator,
+    context: &CheckContext,
+) -> Comparison {
+    // Rows are files of the first submission, columns are files of the second one
+    let matrix: Vec<Vec<Comparison>> = first
+        .files
+        .iter()
+        .map(|first_file| {
+            second
+                .files
+                .iter()
+                .map(|second_file| comparator.compare(first_file, second_file, context))
+                .collect()
+        })
+        .collect();
+
+    let similarity_at = |row: usize, column: usize| {
+        matrix
+            .get(row)
+            .and_then(|comparisons| comparisons.get(column))
+            .map_or(0.0, |comparison| comparison.similarity)
+    };
+
+    // Best match of every file: (row, column, similarity)
+    let mut best: Vec<(usize, usize, f64)> = Vec::new();
+    let mut weighted_sum = 0.0;
+    let mut total_size = 0;
+    for (row, size) in first.sizes.iter().enumerate() {
+        let column =
+            best_match((0..second.files.len()).map(|column| similarity_at(row, column)));
+        if let Some((column, similarity)) = column {
+            best.push((row, column, similarity));
+            weighted_sum += similarity * *size as f64;
+        }
+        total_size += size;
+    }
+    for (column, size) in second.sizes.iter().enumerate() {
+        let row =
+            best_match((0..first.files.len()).map(|row| similarity_at(row, column)));
+        if let Some((row, similarity)) = row {
+            best.push((row, column, similarity));
+            weighted_sum += similarity * *size as f64;
+        }
+        total_size += size;
+    }
+
+    best.sort_by(|first, second| {
+        second
+            .2
+            .total_cmp(&first.2)
+            .then((first.0, first.1).cmp(&(second.0, second.1)))
+    });
+    best.dedup_by_key(|(row, column, _)| (*row, *column));
+    // Files without any match are not worth showing
+    best.retain(|(_, _, similarity)| *similarity > 0.0);
+
+    let file_path = |submission: &SplitSubmission, index: usize| {
+        submission
+            .files
+            .get(index)
+            .and_then(|file| file.files.first())
+            .map(|file| file.relative_path.clone())
+            .unwrap_or_default()
+    };
+
+    let mut regions = Vec::new();
+    let mut files = Vec::new();
+    // File pairs may be scored by different comparators, see `CompressionDistance`
+    let mut algorithms = Vec::new();
+    for (row, column, similarity) in best {
+        if let Some(comparison) = matrix
+            .get(row)
+            .and_then(|comparisons| comparisons.get(column))
+        {
+            regions.extend(comparison.regions.iter().cloned());
+            algorithms.push(comparison.algorithm.as_str());
+        }
+        files.push(FilePair {
+            first: file_path(first, row),
+            second: file_path(second, column),
+            similarity,
+        });
+    }
+
+    Comparison {
+        similarity: if total_size > 0 {
+            weighted_sum / total_size as f64
+        } else {
+            0.0
+        },
+        regions,
+        files,
+        algorithm: if algorithms.is_empty() {
+            comparator.name().to_string()
+        } else {
+            algorithms.sort_unstable();
+            algorithms.dedup();
+            algorithms.join(", ")
+        },
+    }
+}
+
+/// Index and similarity of the most similar file, the first one wins ties.
+fn best_match(similarities: impl Iterator<Item = f64>) -> Option<(usize, f64)> {
+    similarities
+        .enumerate()
+        .fold(None, |best, (index, similarity)| match best {
+            Some((_, best_similarity)) if best_similarity >= similarity => best,
+            _ => Some((index, similarity)),
+        })
+}
@@ -0,0 +1,559 @@
+use crate::compare::context::CheckContext;
+use crate::compare::hash::{self, StableHasher};
+use crate::compare::language::Language;
+use crate::compare::region::MatchRegion;
+use crate::compare::{Comparator, Comparison, Match, Span};
+use crate::models::submission::{CodeFile, Submission};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tree_sitter::{Node, Parser};
+
+/// Structural comparison of syntax trees.
+/// Subtrees are hashed after normalization, so renaming, reordering definitions and imports
+/// and swapping if/else branches (with negated condition) keep the same hash.
+/// Statements keep their order, as they usually depend on the previous ones.
+/// Supports Python, C, C++ and Java. Submissions without such files are compared by winnowing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AstComparator {
+    // Smaller subtrees (in nodes) are too common to count as a match
+    pub minimum_subtree_size: usize,
+}
+
+impl Default for AstComparator {
+    fn default() -> Self {
+        Self {
+            minimum_subtree_size: 8,
+        }
+    }
+}
+
+/// Syntax tree of a file, reduced to normalized hashes of its subtrees.
+#[derive(Debug, Clone, Default)]
+pub struct SyntaxTree {
+    // Pre-order, so every subtree is a continuous range starting from its root
+    nodes: Vec<SyntaxNode>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SyntaxNode {
+    hash: u64,
+
+    // Amount of nodes in the subtree, including this one
+    size: usize,
+
+    // Byte offsets in the file content
+    start: usize,
+    end: usize,
+}
+
+// Location of a subtree: index of the file in the submission and index of the node
+type SubtreeLocation = (usize, usize);
+
+// Definitions and imports of these nodes are compared regardless of their order.
+// Python class bodies are blocks, other blocks hold statements (see `is_unordered`).
+const UNORDERED_KINDS: &[&str] = &[
+    "module",
+    "program",
+    "translation_unit",
+    "declaration_list",
+    "field_declaration_list",
+    "class_body",
+];
+
+// Items that don't depend on their neighbours, so their order doesn't matter
+const INDEPENDENT_KINDS: &[&str] = &[
+    // Python
+    "function_definition",
+    "class_definition",
+    "decorated_definition",
+    "import_statement",
+    "import_from_statement",
+    // C and C++
+    "preproc_include",
+    "type_definition",
+    "field_declaration",
+    "template_declaration",
+    "namespace_definition",
+    // Java
+    "import_declaration",
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "method_declaration",
+    "constructor_declaration",
+];
+
+// Nodes with a single child that are the same code as the child itself
+const TRANSPARENT_KINDS: &[&str] = &[
+    "parenthesized_expression",
+    "condition_clause",
+    "else_clause",
+];
+
+const PUNCTUATION: &[&str] = &["(", ")", "{", "}", "[", "]", ";", ",", ":"];
+
+impl Comparator for AstComparator {
+    fn name(&self) -> &str {
+        "AST"
+    }
+
+    fn compare(
+        &self, first: &Submission, second: &Submission, context: &CheckContext,
+    ) -> Comparison {
+        let first_trees = Self::syntax_trees(first, context);
+        let second_trees = Self::syntax_trees(second, context);
+        if first_trees.is_empty() || second_trees.is_empty() {
+            let winnowing = context.winnowing();
+            return winnowing.compare(first, second, context);
+        }
+
+        let first_subtrees = self.subtrees(&first_trees);
+        let second_subtrees = self.subtrees(&second_trees);
+        let (first_matched, matches) = self.cover(&first_trees, &second_subtrees);
+        let (second_matched, _) = self.cover(&second_trees, &first_subtrees);
+
+        let total: usize = first_trees
+            .iter()
+            .chain(&second_trees)
+            .map(|(_, tree)| tree.nodes.len())
+            .sum();
+        let similarity = if total > 0 {
+            (first_matched + second_matched) as f64 / total as f64
+        } else {
+            0.0
+        };
+
+        let matches: Vec<Match> = matches
+            .into_iter()
+            .filter_map(|(first_location, second_location)| {
+                Some(Match {
+                    first: Self::span(&first_trees, first_location)?,
+                    second: Self::span(&second_trees, second_location)?,
+                    length: Self::node(&first_trees, first_location)?.size,
+                })
+            })
+            .collect();
+
+        Comparison {
+            similarity,
+            regions: MatchRegion::from_matches(&matches, &first.files, &second.files),
+            files: Vec::new(),
+            algorithm: self.name().to_string(),
+        }
+    }
+}
+
+impl AstComparator {
+    /// Parsed files of the submission with their indexes, unsupported files are skipped.
+    fn syntax_trees(
+        submission: &Submission, context: &CheckContext,
+    ) -> Vec<(usize, Arc<SyntaxTree>)> {
+        submission
+            .files
+            .iter()
+            .enumerate()
+            .filter_map(|(index, file)| Some((index, context.syntax_tree(file)?)))
+            .collect()
+    }
+
+    /// First location of every subtree hash, for subtrees big enough to be compared.
+    fn subtrees(
+        &self, trees: &[(usize, Arc<SyntaxTree>)],
+    ) -> HashMap<u64, SubtreeLocation> {
+        let mut subtrees = HashMap::new();
+        for (file, tree) in trees {
+            for (index, node) in tree.nodes.iter().enumerate() {
+                if node.size >= self.minimum_subtree_size {
+                    subtrees.entry(node.hash).or_insert((*file, index));
+                }
+            }
+        }
+        subtrees
+    }
+
+    /// Finds the largest subtrees present in the other submission.
+    /// Returns the amount of covered nodes and locations of the matched subtrees.
+    fn cover(
+        &self, trees: &[(usize, Arc<SyntaxTree>)], other: &HashMap<u64, SubtreeLocation>,
+    ) -> (usize, Vec<(SubtreeLocation, SubtreeLocation)>) {
+        let mut covered = 0;
+        let mut matches = Vec::new();
+
+        for (file, tree) in trees {
+            let mut index = 0;
+            while let Some(node) = tree.nodes.get(index) {
+                match other.get(&node.hash) {
+                    Some(location) if node.size >= self.minimum_subtree_size => {
+                        covered += node.size;
+                        matches.push(((*file, index), *location));
+                        // Nested subtrees are already covered
+                        index += node.size;
+                    },
+                    _ => index += 1,
+                }
+            }
+        }
+
+        (covered, matches)
+    }
+
+    fn node(
+        trees: &[(usize, Arc<SyntaxTree>)], (file, index): SubtreeLocation,
+    ) -> Option<&SyntaxNode> {
+        trees
+            .iter()
+            .find(|(tree_file, _)| *tree_file == file)
+            .and_then(|(_, tree)| tree.nodes.get(index))
+    }
+
+    fn span(
+        trees: &[(usize, Arc<SyntaxTree>)], location: SubtreeLocation,
+    ) -> Option<Span> {
+        Self::node(trees, location).map(|node| Span {
+            file: location.0,
+            start: node.start,
+            end: node.end,
+        })
+    }
+}
+
+impl SyntaxTree {
+    /// Parses the file, if its language is supported.
+    pub fn parse(file: &CodeFile) -> Option<Self> {
+        let grammar: tree_sitter::Language =
+            match Language::from_extension(&file.extension) {
+                Language::Python => tree_sitter_python::LANGUAGE.into(),
+                Language::C => tree_sitter_c::LANGUAGE.into(),
+                Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+                Language::Java => tree_sitter_java::LANGUAGE.into(),
+                _ => return None,
+            };
+
+        let mut parser = Parser::new();
+        parser.set_language(&grammar).ok()?;
+        let tree = parser.parse(&file.content, None)?;
+
+        Some(Self::from_root(tree.root_node()))
+    }
+
+    fn from_root(root: Node) -> Self {
+        // Pre-order walk over named nodes, comments are left out
+        let mut walked: Vec<(Node, Option<usize>)> = Vec::new();
+        let mut stack = vec![(root, None)];
+        while let Some((node, parent)) = stack.pop() {
+            let index = walked.len();
+            walked.push((node, parent));
+
+            let mut cursor = node.walk();
+            let children: Vec<Node> = node
+                .named_children(&mut cursor)
+                .filter(|child| !is_comment(child))
+                .collect();
+            stack.extend(children.into_iter().rev().map(|child| (child, Some(index))));
+        }
+
+        let indexes: HashMap<usize, usize> = walked
+            .iter()
+            .enumerate()
+            .map(|(index, (node, _))| (node.id(), index))
+            .collect();
+
+        let mut nodes: Vec<SyntaxNode> = walked
+            .iter()
+            .map(|(node, _)| SyntaxNode {
+                hash: 0,
+                size: 1,
+                start: node.start_byte(),
+                end: node.end_byte(),
+            })
+            .collect();
+
+        // Children are walked after their parents, so reverse order computes them first
+        for (index, (node, parent)) in walked.iter().enumerate().rev() {
+            let hash_of = |child: &Node| {
+                indexes
+                    .get(&child.id())
+                    .and_then(|index| nodes.get(*index))
+                    .map(|child| child.hash)
+            };
+            let hash = normalized_hash(node, &hash_of, false);
+
+            let size = nodes.get(index).map_or(1, |node| node.size);
+            if let Some(current) = nodes.get_mut(index) {
+                current.hash = hash;
+            }
+            if let Some(parent) = parent.and_then(|parent| nodes.get_mut(parent)) {
+                parent.size += size;
+            }
+        }
+
+        Self { nodes }
+    }
+}
+
+/// Hash of the node from hashes of its named children, ignoring identifier and literal
+/// values, order of independent items and order of if/else branches.
+/// Comparison of the condition root is canonical, see `CanonicalOperator`.
+fn normalized_hash(
+    node: &Node, hash_of: &dyn Fn(&Node) -> Option<u64>, is_condition: bool,
+) -> u64 {
+    let kind = node.kind();
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node
+        .children(&mut cursor)
+        .filter(|child| !is_comment(child))
+        .collect();
+
+    // Named leaves are identifiers, literals and types, their text is ignored
+    if children.is_empty() {
+        return hash::hash_str(kind);
+    }
+
+    let named: Vec<u64> = children
+        .iter()
+        .filter(|child| child.is_named())
+        .filter_map(hash_of)
+        .collect();
+
+    if TRANSPARENT_KINDS.contains(&kind)
+        && let [single] = named.as_slice()
+    {
+        return *single;
+    }
+
+    let mut hasher = StableHasher::default();
+    hasher.write(kind.as_bytes());
+
+    if kind == "if_statement"
+        && let Some(condition) = node.child_by_field_name("condition")
+        && let Some(consequence) = node.child_by_field_name("consequence")
+        && let Some(alternative) = node.child_by_field_name("alternative")
+        // Chains of Python elif clauses are not simple two-way branches
+        && alternative.kind() != "elif_clause"
+        && let Some(consequence) = hash_of(&consequence)
+        && let Some(alternative) = hash_of(&alternative)
+    {
+        // Negated condition is the positive one with swapped branches
+        let (condition, is_negated) = strip_negation(condition);
+        let is_negated = is_negated != is_negated_comparison(&condition);
+        let (first, second) = if is_negated {
+            (alternative, consequence)
+        } else {
+            (consequence, alternative)
+        };
+
+        hasher.write_u64(normalized_hash(&condition, hash_of, true));
+        hasher.write_u64(first);
+        hasher.write_u64(second);
+        return hasher.finish();
+    }
+
+    if is_unordered(node) {
+        // Independent items are sorted, statements between them keep their order
+        let mut independent = Vec::new();
+        let mut ordered = Vec::new();
+        for child in children.iter().filter(|child| child.is_named()) {
+            if let Some(hash) = hash_of(child) {
+                if INDEPENDENT_KINDS.contains(&child.kind()) {
+                    independent.push(hash);
+                } else {
+                    ordered.push(hash);
+                }
+            }
+        }
+        independent.sort_unstable();
+        independent
+            .iter()
+            .chain(&ordered)
+            .for_each(|hash| hasher.write_u64(*hash));
+        return hasher.finish();
+    }
+
+    let mut children = children;
+    if is_condition
+        && comparison_operator(node)
+            .is_some_and(|operator| CanonicalOperator::new(operator.kind()).is_flipped)
+    {
+        children.reverse();
+    }
+
+    for child in &children {
+        if child.is_named() {
+            if let Some(hash) = hash_of(child) {
+                hasher.write_u64(hash);
+            }
+        } else if !PUNCTUATION.contains(&child.kind()) {
+            let operator = if is_condition {
+                CanonicalOperator::new(child.kind()).operator
+            } else {
+                child.kind()
+            };
+            hasher.write(operator.as_bytes());
+        }
+    }
+    hasher.finish()
+}
+
+fn is_unordered(node: &Node) -> bool {
+    UNORDERED_KINDS.contains(&node.kind())
+        || (node.kind() == "block"
+            && node
+                .parent()
+                .is_some_and(|parent| parent.kind() == "class_definition"))
+}
+
+/// Condition without parentheses and logical negation.
+/// Returns true if the condition was negated an odd amount of times.
+fn strip_negation(mut node: Node) -> (Node, bool) {
+    let mut is_negated = false;
+    loop {
+        let inner = match node.kind() {
+            kind if TRANSPARENT_KINDS.contains(&kind)
+                && node.named_child_count() == 1 =>
+            {
+                node.named_child(0)
+            },
+            "not_operator" => {
+                is_negated = !is_negated;
+                node.child_by_field_name("argument")
+            },
+            "unary_expression"
+                if node
+                    .child_by_field_name("operator")
+                    .is_some_and(|operator| operator.kind() == "!") =>
+            {
+                is_negated = !is_negated;
+                node.child_by_field_name("argument")
+                    .or_else(|| node.child_by_field_name("operand"))
+            },
+            _ => None,
+        };
+
+        match inner {
+            Some(inner) => node = inner,
+            None => return (node, is_negated),
+        }
+    }
+}
+
+/// The node is a single comparison with a negated operator, e.g. `a != b`.
+fn is_negated_comparison(node: &Node) -> bool {
+    comparison_operator(node)
+        .is_some_and(|operator| CanonicalOperator::new(operator.kind()).is_negated)
+}
+
+/// Operator of the node, if it's the only one, e.g. `<` of `a < b` but not of `a < b < c`.
+fn comparison_operator<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    let operators: Vec<Node> = node
+        .children(&mut cursor)
+        .filter(|child| !child.is_named() && !PUNCTUATION.contains(&child.kind()))
+        .collect();
+
+    match operators.as_slice() {
+        [operator] => Some(*operator),
+        _ => None,
+    }
+}
+
+/// Comparison reduced to `<`, `==`, `is` or `in`: `b > a` is `a < b` with flipped
+/// operands, and `a >= b` is the negation of `a < b`.
+struct CanonicalOperator<'a> {
+    operator: &'a str,
+
+    // The comparison is the negation of the canonical one
+    is_negated: bool,
+
+    // Operands are swapped relative to the canonical comparison
+    is_flipped: bool,
+}
+
+impl<'a> CanonicalOperator<'a> {
+    fn new(operator: &'a str) -> Self {
+        let (operator, is_negated, is_flipped) = match operator {
+            "<" => ("<", false, false),
+            ">" => ("<", false, true),
+            ">=" => ("<", true, false),
+            "<=" => ("<", true, true),
+            "==" => ("==", false, false),
+            "!=" => ("==", true, false),
+            "is" => ("is", false, false),
+            "is not" => ("is", true, false),
+            "in" => ("in", false, false),
+            "not in" => ("in", true, false),
+            _ => (operator, false, false),
+        };
+
+        Self {
+            operator,
+            is_negated,
+            is_flipped,
+        }
+    }
+}
+
+fn is_comment(node: &Node) -> bool {
+    node.kind().contains("comment")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root_hash(code: &str) -> Option<u64> {
+        let file = CodeFile {
+            relative_path: "main.py".to_string(),
+            content: code.to_string(),
+            extension: "py".to_string(),
+        };
+        SyntaxTree::parse(&file)?
+            .nodes
+            .first()
+            .map(|node| node.hash)
+    }
+
+    fn assert_same(first: &str, second: &str) {
+        assert!(root_hash(first).is_some());
+        assert_eq!(root_hash(first), root_hash(second));
+    }
+
+    fn assert_different(first: &str, second: &str) {
+        assert!(root_hash(first).is_some());
+        assert_ne!(root_hash(first), root_hash(second));
+    }
+
+    #[test]
+    fn definitions_are_unordered() {
+        assert_same(
+            "import os\ndef f():\n    return 1\nclass A:\n    def g(self):\n        pass\n    def h(self):\n        return 2\n",
+            "class A:\n    def h(self):\n        return 2\n    def g(self):\n        pass\ndef f():\n    return 1\nimport os\n",
+        );
+    }
+
+    #[test]
+    fn statements_are_ordered() {
+        assert_different(
+            "def f():\n    a = read()\n    b = a + 1\n    return b\n",
+            "def f():\n    return b\n    b = a + 1\n    a = read()\n",
+        );
+        assert_different("a = read()\nprint(a)\n", "print(a)\na = read()\n");
+    }
+
+    #[test]
+    fn flipped_comparison_is_the_same() {
+        let less = "if x < 10:\n    y = 1\nelse:\n    return\n";
+        assert_same(less, "if 10 > x:\n    y = 1\nelse:\n    return\n");
+        assert_different(less, "if x > 10:\n    y = 1\nelse:\n    return\n");
+    }
+
+    #[test]
+    fn negated_condition_swaps_branches() {
+        let positive = "if x < 10:\n    y = 1\nelse:\n    return\n";
+        assert_same(positive, "if not x < 10:\n    return\nelse:\n    y = 1\n");
+        assert_same(positive, "if x >= 10:\n    return\nelse:\n    y = 1\n");
+        assert_same(positive, "if 10 <= x:\n    return\nelse:\n    y = 1\n");
+        assert_different(positive, "if x >= 10:\n    y = 1\nelse:\n    return\n");
+    }
+}
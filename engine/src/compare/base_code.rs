@@ -0,0 +1,51 @@
+use crate::models::base_code::BaseCode;
+use crate::models::database::DatabaseSettings;
+use crate::models::submission::Submission;
+
+/// Blanks out fragments of the submission that match the applicable base code.
+/// Blanked characters are replaced by spaces (line breaks are kept),
+/// so byte offsets and line numbers of the remaining code stay the same.
+pub fn exclude_base_code(
+    submission: &Submission, base_code: &[BaseCode], settings: &DatabaseSettings,
+) -> Submission {
+    let mut result = submission.clone();
+    let tiling = settings.comparison.tiling;
+    let normalization = &settings.comparison.normalization;
+
+    for base in base_code
+        .iter()
+        .filter(|base| base.applies_to(&submission.metadata))
+    {
+        let comparison =
+            tiling.compare_file_sets(&result.files, &base.files, normalization);
+
+        for region in comparison.regions {
+            if let Some(code_file) = result
+                .files
+                .iter_mut()
+                .find(|code_file| code_file.relative_path == region.first.file)
+            {
+                code_file.content = blank(
+                    &code_file.content,
+                    region.first.start_offset,
+                    region.first.end_offset,
+                );
+            }
+        }
+    }
+
+    result
+}
+
+fn blank(content: &str, start: usize, end: usize) -> String {
+    content
+        .char_indices()
+        .map(|(index, character)| {
+            if index < start || index >= end || character == '\n' || character == '\r' {
+                character.to_string()
+            } else {
+                " ".repeat(character.len_utf8())
+            }
+        })
+        .collect()
+}
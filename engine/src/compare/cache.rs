@@ -0,0 +1,40 @@
+use crate::compare::hash;
+use crate::compare::winnowing::Fingerprint;
+use crate::models::comparison::ComparisonSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Winnowing fingerprints of file contents, persisted in the database,
+/// so re-checks don't tokenize unchanged files again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FingerprintCache {
+    // Hash of the settings the fingerprints were computed with
+    pub settings_hash: String,
+
+    // Content hash and extension -> fingerprints of the file (file index is always 0)
+    pub files: HashMap<String, Vec<Fingerprint>>,
+}
+
+impl FingerprintCache {
+    pub fn settings_hash(settings: &ComparisonSettings) -> String {
+        let serialized =
+            serde_json::to_string(&(&settings.winnowing, &settings.normalization))
+                .unwrap_or_default();
+        format!("{:016x}", hash::hash_str(&serialized))
+    }
+
+    /// Drops every fingerprint if they were computed with other settings.
+    pub fn validate(&mut self, settings: &ComparisonSettings) {
+        let settings_hash = Self::settings_hash(settings);
+        if self.settings_hash != settings_hash {
+            self.files.clear();
+            self.settings_hash = settings_hash;
+        }
+    }
+
+    /// Keeps only fingerprints of the given contents, so the cache doesn't grow forever.
+    pub fn retain(&mut self, content_hashes: &HashSet<String>) {
+        self.files
+            .retain(|content_hash, _| content_hashes.contains(content_hash));
+    }
+}
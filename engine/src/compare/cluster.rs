@@ -0,0 +1,359 @@
+use crate::models::result::CheckResult;
+use crate::models::submission::SubmissionMetadata;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Group of submissions sharing one solution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cluster {
+    pub members: Vec<ClusterMember>,
+    pub statistics: ClusterStatistics,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterMember {
+    pub submission_id: String,
+    pub metadata: SubmissionMetadata,
+}
+
+/// Similarities between every two members of the cluster.
+/// Pairs without a result (e.g. skipped by LSH) count as 0.0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClusterStatistics {
+    pub mean_similarity: f64,
+    pub min_similarity: f64,
+    pub max_similarity: f64,
+
+    // Share of member pairs with similarity above the threshold, in range 0.0..=1.0
+    pub density: f64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClusterMethod {
+    /// Submissions linked by any chain of similar pairs.
+    /// Fast, but one borderline pair can join two unrelated groups.
+    ConnectedComponents,
+
+    /// Hierarchical clustering, merges groups while their average similarity
+    /// stays above the threshold.
+    #[default]
+    AverageLinkage,
+}
+
+/// Groups submissions of the checked database by similarity of their pairs.
+/// Cross-database and expected pairs are not clustered. Only groups of two and more are returned,
+/// ordered from the largest.
+pub fn cluster(
+    result: &CheckResult, method: ClusterMethod, threshold: f64,
+) -> Vec<Cluster> {
+    let graph = SimilarityGraph::new(result);
+
+    let groups = match method {
+        ClusterMethod::ConnectedComponents => graph.connected_components(threshold),
+        ClusterMethod::AverageLinkage => graph.average_linkage(threshold),
+    };
+
+    let mut clusters: Vec<Cluster> = groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| graph.cluster(&group, threshold))
+        .collect();
+    clusters.sort_by(|first, second| {
+        second.members.len().cmp(&first.members.len()).then(
+            second
+                .statistics
+                .mean_similarity
+                .total_cmp(&first.statistics.mean_similarity),
+        )
+    });
+    clusters
+}
+
+struct SimilarityGraph<'a> {
+    // Submission ids with their metadata
+    nodes: Vec<(&'a str, &'a SubmissionMetadata)>,
+
+    // Keys are ordered pairs of node indexes
+    edges: HashMap<(usize, usize), f64>,
+}
+
+impl<'a> SimilarityGraph<'a> {
+    fn new(result: &'a CheckResult) -> Self {
+        let mut nodes = Vec::new();
+        let mut indexes: HashMap<&str, usize> = HashMap::new();
+        let mut index = |id: &'a str, metadata: &'a SubmissionMetadata| {
+            *indexes.entry(id).or_insert_with(|| {
+                nodes.push((id, metadata));
+                nodes.len() - 1
+            })
+        };
+
+        let mut edges = HashMap::new();
+        for pair in result.in_course_pairs().filter(|pair| !pair.is_expected) {
+            let first = index(&pair.first_id, &pair.first);
+            let second = index(&pair.second_id, &pair.second);
+            if first != second {
+                edges.insert(ordered(first, second), pair.similarity);
+            }
+        }
+
+        Self { nodes, edges }
+    }
+
+    fn similarity(&self, first: usize, second: usize) -> f64 {
+        self.edges
+            .get(&ordered(first, second))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn connected_components(&self, threshold: f64) -> Vec<Vec<usize>> {
+        let mut parents: Vec<usize> = (0..self.nodes.len()).collect();
+
+        for (&(first, second), &similarity) in &self.edges {
+            if similarity >= threshold {
+                let first = find_root(&mut parents, first);
+                let second = find_root(&mut parents, second);
+                if let Some(parent) = parents.get_mut(first.max(second)) {
+                    *parent = first.min(second);
+                }
+            }
+        }
+
+        let mut components: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for node in 0..self.nodes.len() {
+            let root = find_root(&mut parents, node);
+            components.entry(root).or_default().push(node);
+        }
+        components.into_values().collect()
+    }
+
+    fn average_linkage(&self, threshold: f64) -> Vec<Vec<usize>> {
+        let mut groups: BTreeMap<usize, Vec<usize>> = (0..self.nodes.len())
+            .map(|node| (node, vec![node]))
+            .collect();
+
+        // Sums of similarities between members of two groups, only non-zero ones
+        let mut sums: HashMap<(usize, usize), f64> = self
+            .edges
+            .iter()
+            .filter(|(_, similarity)| **similarity > 0.0)
+            .map(|(&key, &similarity)| (key, similarity))
+            .collect();
+
+        loop {
+            let size = |group: usize| groups.get(&group).map_or(1, Vec::len) as f64;
+            let closest = sums
+                .iter()
+                .map(|(&(first, second), &sum)| {
+                    (first, second, sum / (size(first) * size(second)))
+                })
+                .filter(|(_, _, average)| *average >= threshold)
+                .max_by(|first, second| {
+                    first.2.total_cmp(&second.2).then(
+                        // Same averages are merged in a stable order
+                        (second.0, second.1).cmp(&(first.0, first.1)),
+                    )
+                });
+            let Some((kept, merged, _)) = closest else {
+                break;
+            };
+
+            // Sum with the merged group = sum with the kept + sum with the absorbed one
+            let absorbed: Vec<((usize, usize), f64)> = sums
+                .iter()
+                .filter(|((first, second), _)| *first == merged || *second == merged)
+                .map(|(&key, &sum)| (key, sum))
+                .collect();
+            for (key, sum) in absorbed {
+                sums.remove(&key);
+                let other = if key.0 == merged { key.1 } else { key.0 };
+                if other != kept {
+                    *sums.entry(ordered(kept, other)).or_default() += sum;
+                }
+            }
+
+            if let Some(members) = groups.remove(&merged)
+                && let Some(group) = groups.get_mut(&kept)
+            {
+                group.extend(members);
+            }
+        }
+
+        groups.into_values().collect()
+    }
+
+    fn cluster(&self, group: &[usize], threshold: f64) -> Cluster {
+        let similarities: Vec<f64> = group
+            .iter()
+            .enumerate()
+            .flat_map(|(index, &first)| {
+                group
+                    .iter()
+                    .skip(index + 1)
+                    .map(move |&second| self.similarity(first, second))
+            })
+            .collect();
+
+        let count = similarities.len().max(1) as f64;
+        let statistics = ClusterStatistics {
+            mean_similarity: similarities.iter().sum::<f64>() / count,
+            min_similarity: similarities.iter().copied().fold(f64::NAN, f64::min),
+            max_similarity: similarities.iter().copied().fold(f64::NAN, f64::max),
+            density: similarities
+                .iter()
+                .filter(|similarity| **similarity >= threshold)
+                .count() as f64
+                / count,
+        };
+
+        Cluster {
+            members: group
+                .iter()
+                .filter_map(|node| self.nodes.get(*node))
+                .map(|(id, metadata)| ClusterMember {
+                    submission_id: id.to_string(),
+                    metadata: (*metadata).clone(),
+                })
+                .collect(),
+            statistics,
+        }
+    }
+}
+
+fn ordered(first: usize, second: usize) -> (usize, usize) {
+    (first.min(second), first.max(second))
+}
+
+fn find_root(parents: &mut [usize], node: usize) -> usize {
+    let mut root = node;
+    while let Some(&parent) = parents.get(root)
+        && parent != root
+    {
+        root = parent;
+    }
+
+    // Path compression
+    let mut current = node;
+    while let Some(parent) = parents.get_mut(current)
+        && *parent != root
+    {
+        current = std::mem::replace(parent, root);
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::comparison::ComparisonSettings;
+    use crate::models::result::{DatabaseOrigin, PairResult};
+    use crate::models::verdict::Verdict;
+
+    fn metadata(id: &str) -> SubmissionMetadata {
+        SubmissionMetadata {
+            student_name: id.to_string(),
+            assignment_title: None,
+        }
+    }
+
+    fn pair(first: &str, second: &str, similarity: f64) -> PairResult {
+        PairResult {
+            first_id: first.to_string(),
+            second_id: second.to_string(),
+            first: metadata(first),
+            second: metadata(second),
+            first_version: 1,
+            second_version: 1,
+            origin: None,
+            similarity,
+            algorithm: String::new(),
+            regions: Vec::new(),
+            regions_discarded: false,
+            files: Vec::new(),
+            is_expected: false,
+            verdict: Verdict::default(),
+        }
+    }
+
+    fn result(pairs: Vec<PairResult>) -> CheckResult {
+        CheckResult::new(
+            Vec::new(),
+            Vec::new(),
+            pairs,
+            &ComparisonSettings::default(),
+        )
+    }
+
+    fn members(clusters: &[Cluster]) -> Vec<Vec<&str>> {
+        clusters
+            .iter()
+            .map(|cluster| {
+                let mut ids: Vec<&str> = cluster
+                    .members
+                    .iter()
+                    .map(|member| member.submission_id.as_str())
+                    .collect();
+                ids.sort_unstable();
+                ids
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chains_join_only_connected_components() {
+        let chain = result(vec![pair("a", "b", 0.9), pair("b", "c", 0.8)]);
+
+        let clusters = cluster(&chain, ClusterMethod::ConnectedComponents, 0.5);
+        assert_eq!(members(&clusters), [["a", "b", "c"]]);
+
+        // The average similarity of c with {a, b} is (0.8 + 0.0) / 2
+        let clusters = cluster(&chain, ClusterMethod::AverageLinkage, 0.5);
+        assert_eq!(members(&clusters), [["a", "b"]]);
+    }
+
+    #[test]
+    fn largest_cluster_comes_first() {
+        let pairs = result(vec![
+            pair("a", "b", 0.95),
+            pair("c", "d", 0.7),
+            pair("c", "e", 0.7),
+            pair("d", "e", 0.6),
+            pair("a", "c", 0.1),
+        ]);
+        let clusters = cluster(&pairs, ClusterMethod::AverageLinkage, 0.5);
+        assert_eq!(members(&clusters), [vec!["c", "d", "e"], vec!["a", "b"]]);
+    }
+
+    #[test]
+    fn expected_and_cross_database_pairs_are_skipped() {
+        let mut expected = pair("a", "b", 0.9);
+        expected.is_expected = true;
+        let mut reference = pair("a", "c", 0.9);
+        reference.origin = Some(DatabaseOrigin {
+            id: "reference".to_string(),
+            name: "Reference".to_string(),
+        });
+
+        let pairs = result(vec![expected, reference]);
+        assert!(cluster(&pairs, ClusterMethod::ConnectedComponents, 0.5).is_empty());
+    }
+
+    #[test]
+    fn statistics_count_missing_pairs_as_zero() {
+        let chain = result(vec![pair("a", "b", 0.9), pair("b", "c", 0.6)]);
+        let clusters = cluster(&chain, ClusterMethod::ConnectedComponents, 0.5);
+        let statistics = clusters.first().map(|cluster| cluster.statistics);
+
+        assert_eq!(
+            statistics,
+            Some(ClusterStatistics {
+                mean_similarity: 0.5,
+                min_similarity: 0.0,
+                max_similarity: 0.9,
+                density: 2.0 / 3.0,
+            })
+        );
+    }
+}
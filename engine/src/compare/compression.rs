@@ -0,0 +1,127 @@
+use crate::compare::context::CheckContext;
+use crate::compare::language::Language;
+use crate::compare::normalize::normalize_text;
+use crate::compare::{Comparator, Comparison};
+use crate::models::comparison::NormalizationSettings;
+use crate::models::submission::Submission;
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Normalized compression distance (NCD) over normalized content.
+/// Language-agnostic, so it suits file types without a tokenizer (SQL, HTML, assembly).
+/// Submissions made only of such files are compared by it whatever the selected algorithm.
+/// Matched regions are not found. Deflate sees only the last 32 KiB,
+/// so very large submissions are scored lower than they should be.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionDistance {
+    // Deflate level, 0..=9
+    pub level: u32,
+
+    // Shared keywords and markup make unrelated files of one kind compress together
+    // better than random text. Raw similarity up to this value is scored as 0,
+    // the rest is stretched to 0.0..=1.0.
+    pub baseline: f64,
+}
+
+impl Default for CompressionDistance {
+    fn default() -> Self {
+        Self {
+            level: 9,
+            baseline: 0.45,
+        }
+    }
+}
+
+impl Comparator for CompressionDistance {
+    fn name(&self) -> &str {
+        "Normalized Compression Distance"
+    }
+
+    fn compare(
+        &self, first: &Submission, second: &Submission, context: &CheckContext,
+    ) -> Comparison {
+        let normalization = &context.settings.comparison.normalization;
+
+        Comparison {
+            similarity: self.similarity(
+                &Self::normalize(first, normalization),
+                &Self::normalize(second, normalization),
+            ),
+            regions: Vec::new(),
+            files: Vec::new(),
+            algorithm: self.name().to_string(),
+        }
+    }
+}
+
+impl CompressionDistance {
+    /// Submission has files, and none of them has a tokenizer.
+    pub fn suits(submission: &Submission) -> bool {
+        !submission.files.is_empty()
+            && submission.files.iter().all(|file| {
+                Language::from_extension(&file.extension) == Language::Generic
+            })
+    }
+
+    /// 1 - NCD rescaled against the baseline,
+    /// where NCD(x, y) = (C(xy) - min(C(x), C(y))) / max(C(x), C(y)).
+    pub fn similarity(&self, first: &[u8], second: &[u8]) -> f64 {
+        if first.is_empty() || second.is_empty() {
+            return 0.0;
+        }
+        // Compressor overhead keeps the raw score of identical content below 1
+        if first == second {
+            return 1.0;
+        }
+
+        let range = (1.0 - self.baseline).max(f64::EPSILON);
+        ((self.raw_similarity(first, second) - self.baseline) / range).clamp(0.0, 1.0)
+    }
+
+    fn raw_similarity(&self, first: &[u8], second: &[u8]) -> f64 {
+        let first_size = self.compressed_size(&[first]);
+        let second_size = self.compressed_size(&[second]);
+        // Both orders are compressed, so the score is symmetric
+        let joined_size = self
+            .compressed_size(&[first, second])
+            .min(self.compressed_size(&[second, first]));
+
+        let smaller = first_size.min(second_size) as f64;
+        let larger = first_size.max(second_size).max(1) as f64;
+        let distance = (joined_size as f64 - smaller) / larger;
+
+        (1.0 - distance).clamp(0.0, 1.0)
+    }
+
+    fn compressed_size(&self, parts: &[&[u8]]) -> usize {
+        let mut encoder =
+            DeflateEncoder::new(Vec::new(), Compression::new(self.level.min(9)));
+        for part in parts {
+            if encoder.write_all(part).is_err() {
+                return 0;
+            }
+        }
+        encoder.finish().map_or(0, |compressed| compressed.len())
+    }
+
+    /// Normalized content of all files, ordered by path.
+    fn normalize(
+        submission: &Submission, normalization: &NormalizationSettings,
+    ) -> Vec<u8> {
+        let mut files: Vec<_> = submission.files.iter().collect();
+        files.sort_by(|first, second| first.relative_path.cmp(&second.relative_path));
+
+        files
+            .into_iter()
+            .flat_map(|file| {
+                let language = Language::from_extension(&file.extension);
+                let mut content = normalize_text(&file.content, language, normalization);
+                content.push('\n');
+                content.into_bytes()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,137 @@
+use crate::compare::ast::SyntaxTree;
+use crate::compare::cache::FingerprintCache;
+use crate::compare::compression::CompressionDistance;
+use crate::compare::winnowing::{Fingerprint, SubmissionFingerprints, Winnowing};
+use crate::models::database::DatabaseSettings;
+use crate::models::submission::{CodeFile, Submission};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Everything comparators may need besides the submissions:
+/// database settings and shared data computed during the check.
+#[derive(Debug)]
+pub struct CheckContext<'a> {
+    pub settings: &'a DatabaseSettings,
+
+    fingerprint_cache: RwLock<FingerprintCache>,
+
+    // Parsed files by content, None for unsupported languages. Not persisted.
+    syntax_trees: RwLock<HashMap<String, Option<Arc<SyntaxTree>>>>,
+}
+
+impl<'a> CheckContext<'a> {
+    pub fn new(settings: &'a DatabaseSettings) -> Self {
+        Self::with_cache(settings, FingerprintCache::default())
+    }
+
+    /// Reuses fingerprints computed earlier with the same settings.
+    pub fn with_cache(
+        settings: &'a DatabaseSettings, mut cache: FingerprintCache,
+    ) -> Self {
+        cache.validate(&settings.comparison);
+
+        Self {
+            settings,
+            fingerprint_cache: RwLock::new(cache),
+            syntax_trees: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn into_cache(self) -> FingerprintCache {
+        self.fingerprint_cache
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Fingerprints of the submission, computed with the winnowing settings of the database.
+    pub fn fingerprint_submission(
+        &self, submission: &Submission,
+    ) -> SubmissionFingerprints {
+        SubmissionFingerprints::new(
+            submission
+                .files
+                .iter()
+                .enumerate()
+                .flat_map(|(index, file)| self.fingerprint_file(file, index))
+                .collect(),
+        )
+    }
+
+    pub fn fingerprint_file(&self, file: &CodeFile, index: usize) -> Vec<Fingerprint> {
+        let content_hash = Self::cache_key(file);
+
+        let cached = self
+            .fingerprint_cache
+            .read()
+            .ok()
+            .and_then(|cache| cache.files.get(&content_hash).cloned());
+        let fingerprints = match cached {
+            Some(fingerprints) => fingerprints,
+            None => {
+                let fingerprints = self.winnowing().fingerprint_file(
+                    file,
+                    0,
+                    &self.settings.comparison.normalization,
+                );
+                if let Ok(mut cache) = self.fingerprint_cache.write() {
+                    cache.files.insert(content_hash, fingerprints.clone());
+                }
+                fingerprints
+            },
+        };
+
+        fingerprints
+            .into_iter()
+            .map(|fingerprint| Fingerprint {
+                file: index,
+                ..fingerprint
+            })
+            .collect()
+    }
+
+    /// Syntax tree of the file, if its language is supported by the AST comparator.
+    pub fn syntax_tree(&self, file: &CodeFile) -> Option<Arc<SyntaxTree>> {
+        let key = Self::cache_key(file);
+
+        let cached = self
+            .syntax_trees
+            .read()
+            .ok()
+            .and_then(|trees| trees.get(&key).cloned());
+        match cached {
+            Some(tree) => tree,
+            None => {
+                let tree = SyntaxTree::parse(file).map(Arc::new);
+                if let Ok(mut trees) = self.syntax_trees.write() {
+                    trees.insert(key, tree.clone());
+                }
+                tree
+            },
+        }
+    }
+
+    // Tokenization depends on the language, so the extension is a part of the key
+    fn cache_key(file: &CodeFile) -> String {
+        format!("{}.{}", file.content_hash(), file.extension.to_lowercase())
+    }
+
+    pub fn winnowing(&self) -> &Winnowing {
+        &self.settings.comparison.winnowing
+    }
+
+    pub fn compression(&self) -> &CompressionDistance {
+        &self.settings.comparison.compression
+    }
+
+    /// Drops cached fingerprints of files that are no longer part of the check.
+    pub(crate) fn retain_cache(&self, submissions: &[Submission]) {
+        let content_hashes = submissions
+            .iter()
+            .flat_map(|submission| &submission.files)
+            .map(Self::cache_key)
+            .collect();
+        if let Ok(mut cache) = self.fingerprint_cache.write() {
+            cache.retain(&content_hashes);
+        }
+    }
+}
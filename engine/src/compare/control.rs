@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Progress reporting and cooperative cancellation of a running check.
+/// Clones share the same state, so one clone can be moved into the worker thread,
+/// and another one kept to cancel the check (e.g. from the GUI).
+#[derive(Clone, Default)]
+pub struct CheckControl {
+    is_cancelled: Arc<AtomicBool>,
+    on_progress: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+impl std::fmt::Debug for CheckControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CheckControl")
+            .field("is_cancelled", &self.is_cancelled())
+            .field("has_progress_callback", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl CheckControl {
+    /// Callback is called from worker threads, roughly every percent of progress.
+    pub fn with_progress(
+        mut self, callback: impl Fn(Progress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn report(&self, progress: Progress) {
+        if let Some(callback) = &self.on_progress {
+            callback(progress);
+        }
+    }
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.completed as f32 / self.total as f32
+    }
+
+    // Reporting every pair would flood the receiver, so only percent steps are reported
+    pub(crate) fn should_report(&self) -> bool {
+        let step = (self.total / 100).max(1);
+        self.completed.is_multiple_of(step) || self.completed == self.total
+    }
+}
@@ -0,0 +1,50 @@
+//! Stable 64-bit FNV-1a hashing.
+//! `std::hash::DefaultHasher` is not guaranteed to be stable between Rust releases,
+//! so everything that may be persisted or compared across runs is hashed here.
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher {
+    state: u64,
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self {
+            state: OFFSET_BASIS,
+        }
+    }
+}
+
+impl StableHasher {
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= u64::from(*byte);
+            self.state = self.state.wrapping_mul(PRIME);
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+pub fn hash_str(value: &str) -> u64 {
+    let mut hasher = StableHasher::default();
+    hasher.write(value.as_bytes());
+    hasher.finish()
+}
+
+pub fn hash_sequence(values: &[u64]) -> u64 {
+    let mut hasher = StableHasher::default();
+    for value in values {
+        hasher.write_u64(*value);
+    }
+    hasher.finish()
+}
@@ -0,0 +1,468 @@
+use strum_macros::EnumIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum Language {
+    C,
+    Cpp,
+    Java,
+    CSharp,
+    Python,
+    JavaScript,
+    Go,
+    Rust,
+
+    /// Any other accepted extension. Comments and keywords are unknown.
+    Generic,
+}
+
+/// Lexical rules of a language, enough to split the code into tokens.
+#[derive(Debug)]
+pub struct Syntax {
+    pub line_comments: &'static [&'static str],
+    pub block_comments: &'static [(&'static str, &'static str)],
+
+    // Ordered from the longest, so triple quotes win over single ones
+    pub string_delimiters: &'static [&'static str],
+
+    // Reserved words only. Builtin functions and types, like `len` or `Some`,
+    // are identifiers, so they are normalized like any other name.
+    pub keywords: &'static [&'static str],
+
+    // Statements that only bring other code into scope
+    pub import_prefixes: &'static [&'static str],
+
+    // Rust lifetimes ('a) look like unterminated character literals
+    pub has_lifetimes: bool,
+}
+
+impl Language {
+    pub fn from_extension(extension: &str) -> Self {
+        match extension.to_lowercase().as_str() {
+            "c" | "h" => Self::C,
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" | "ino" | "mm" => Self::Cpp,
+            "java" => Self::Java,
+            "cs" => Self::CSharp,
+            "py" | "pyw" => Self::Python,
+            "js" | "jsx" | "mjs" | "ts" | "tsx" => Self::JavaScript,
+            "go" => Self::Go,
+            "rs" => Self::Rust,
+            _ => Self::Generic,
+        }
+    }
+
+    pub fn syntax(&self) -> &'static Syntax {
+        match self {
+            Self::C => &C,
+            Self::Cpp => &CPP,
+            Self::Java => &JAVA,
+            Self::CSharp => &CSHARP,
+            Self::Python => &PYTHON,
+            Self::JavaScript => &JAVASCRIPT,
+            Self::Go => &GO,
+            Self::Rust => &RUST,
+            Self::Generic => &GENERIC,
+        }
+    }
+}
+
+const C_COMMENTS: &[(&str, &str)] = &[("/*", "*/")];
+
+const C: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: C_COMMENTS,
+    string_delimiters: &["\"", "'"],
+    keywords: &[
+        "auto", "break", "case", "char", "const", "continue", "default", "do", "double",
+        "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long",
+        "register", "restrict", "return", "short", "signed", "sizeof", "static",
+        "struct", "switch", "typedef", "union", "unsigned", "void", "volatile", "while",
+        "bool", "true", "false", "NULL", "include", "define", "ifdef", "ifndef", "endif",
+        "pragma", "undef", "elif",
+    ],
+    import_prefixes: &["#include", "#import"],
+    has_lifetimes: false,
+};
+
+const CPP: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: C_COMMENTS,
+    string_delimiters: &["\"", "'"],
+    keywords: &[
+        "alignas",
+        "alignof",
+        "auto",
+        "bool",
+        "break",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "const",
+        "constexpr",
+        "const_cast",
+        "continue",
+        "decltype",
+        "default",
+        "delete",
+        "do",
+        "double",
+        "dynamic_cast",
+        "else",
+        "enum",
+        "explicit",
+        "export",
+        "extern",
+        "false",
+        "float",
+        "for",
+        "friend",
+        "goto",
+        "if",
+        "inline",
+        "int",
+        "long",
+        "mutable",
+        "namespace",
+        "new",
+        "noexcept",
+        "nullptr",
+        "operator",
+        "private",
+        "protected",
+        "public",
+        "register",
+        "reinterpret_cast",
+        "return",
+        "short",
+        "signed",
+        "sizeof",
+        "static",
+        "static_assert",
+        "static_cast",
+        "struct",
+        "switch",
+        "template",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typedef",
+        "typeid",
+        "typename",
+        "union",
+        "unsigned",
+        "using",
+        "virtual",
+        "void",
+        "volatile",
+        "while",
+        "override",
+        "final",
+        "NULL",
+        "include",
+        "define",
+        "ifdef",
+        "ifndef",
+        "endif",
+        "pragma",
+        "undef",
+        "elif",
+    ],
+    import_prefixes: &["#include", "#import", "import", "using namespace"],
+    has_lifetimes: false,
+};
+
+const JAVA: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: C_COMMENTS,
+    string_delimiters: &["\"\"\"", "\"", "'"],
+    keywords: &[
+        "abstract",
+        "assert",
+        "boolean",
+        "break",
+        "byte",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "extends",
+        "final",
+        "finally",
+        "float",
+        "for",
+        "goto",
+        "if",
+        "implements",
+        "import",
+        "instanceof",
+        "int",
+        "interface",
+        "long",
+        "native",
+        "new",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "short",
+        "static",
+        "strictfp",
+        "super",
+        "switch",
+        "synchronized",
+        "this",
+        "throw",
+        "throws",
+        "transient",
+        "try",
+        "void",
+        "volatile",
+        "while",
+        "var",
+        "record",
+        "yield",
+        "true",
+        "false",
+        "null",
+    ],
+    import_prefixes: &["import", "package"],
+    has_lifetimes: false,
+};
+
+const CSHARP: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: C_COMMENTS,
+    string_delimiters: &["\"\"\"", "\"", "'"],
+    keywords: &[
+        "abstract",
+        "as",
+        "base",
+        "bool",
+        "break",
+        "byte",
+        "case",
+        "catch",
+        "char",
+        "checked",
+        "class",
+        "const",
+        "continue",
+        "decimal",
+        "default",
+        "delegate",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "event",
+        "explicit",
+        "extern",
+        "false",
+        "finally",
+        "fixed",
+        "float",
+        "for",
+        "foreach",
+        "goto",
+        "if",
+        "implicit",
+        "in",
+        "int",
+        "interface",
+        "internal",
+        "is",
+        "lock",
+        "long",
+        "namespace",
+        "new",
+        "null",
+        "object",
+        "operator",
+        "out",
+        "override",
+        "params",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "ref",
+        "return",
+        "sbyte",
+        "sealed",
+        "short",
+        "sizeof",
+        "stackalloc",
+        "static",
+        "string",
+        "struct",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "uint",
+        "ulong",
+        "unchecked",
+        "unsafe",
+        "ushort",
+        "using",
+        "var",
+        "virtual",
+        "void",
+        "volatile",
+        "while",
+        "async",
+        "await",
+        "get",
+        "set",
+        "yield",
+        "record",
+    ],
+    import_prefixes: &["using"],
+    has_lifetimes: false,
+};
+
+const PYTHON: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[],
+    string_delimiters: &["\"\"\"", "'''", "\"", "'"],
+    keywords: &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break",
+        "class", "continue", "def", "del", "elif", "else", "except", "finally", "for",
+        "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or",
+        "pass", "raise", "return", "try", "while", "with", "yield", "match", "case",
+    ],
+    import_prefixes: &["import", "from"],
+    has_lifetimes: false,
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: C_COMMENTS,
+    string_delimiters: &["`", "\"", "'"],
+    keywords: &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+        "interface",
+        "type",
+        "enum",
+        "implements",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+    ],
+    import_prefixes: &["import"],
+    has_lifetimes: false,
+};
+
+const GO: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: C_COMMENTS,
+    string_delimiters: &["`", "\"", "'"],
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "fallthrough",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "type",
+        "var",
+        "true",
+        "false",
+        "nil",
+    ],
+    import_prefixes: &["import", "package"],
+    has_lifetimes: false,
+};
+
+const RUST: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: C_COMMENTS,
+    string_delimiters: &["\"", "'"],
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+        "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop",
+        "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static",
+        "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+    ],
+    import_prefixes: &["use", "pub use", "extern crate"],
+    has_lifetimes: true,
+};
+
+const GENERIC: Syntax = Syntax {
+    line_comments: &[],
+    block_comments: &[],
+    string_delimiters: &["\"", "'"],
+    keywords: &[],
+    import_prefixes: &[],
+    has_lifetimes: false,
+};
@@ -0,0 +1,184 @@
+use crate::compare::context::CheckContext;
+use crate::models::comparison::LshSettings;
+use crate::models::submission::Submission;
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+/// Finds pairs of submissions likely to be similar, using MinHash signatures
+/// of their winnowing fingerprints and locality-sensitive hashing over signature bands.
+/// Only these pairs need an exact comparison.
+pub fn candidate_pairs(
+    submissions: &[Submission], context: &CheckContext,
+) -> Vec<(usize, usize)> {
+    let lsh = &context.settings.comparison.lsh;
+    let (bands, rows) = lsh.bands_and_rows();
+
+    let signatures: Vec<Option<Vec<u64>>> = submissions
+        .par_iter()
+        .map(|submission| signature(submission, context, bands * rows))
+        .collect();
+
+    // Submissions sharing any band end up in the same bucket
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (index, signature) in signatures.iter().enumerate() {
+        let Some(signature) = signature else {
+            continue;
+        };
+        for (band, values) in signature.chunks(rows).enumerate() {
+            let key = values
+                .iter()
+                .fold(band as u64, |state, value| mix(state ^ value));
+            buckets.entry((band, key)).or_default().push(index);
+        }
+    }
+
+    let mut pairs = BTreeSet::new();
+    for members in buckets.values() {
+        for (position, first) in members.iter().enumerate() {
+            for second in members.iter().skip(position + 1) {
+                pairs.insert(((*first).min(*second), (*first).max(*second)));
+            }
+        }
+    }
+
+    pairs.into_iter().collect()
+}
+
+/// MinHash signature of the submission fingerprints.
+/// Submissions without fingerprints have no signature and never become candidates.
+fn signature(
+    submission: &Submission, context: &CheckContext, length: usize,
+) -> Option<Vec<u64>> {
+    let fingerprints = context.fingerprint_submission(submission);
+    if fingerprints.hashes.is_empty() {
+        return None;
+    }
+
+    let signature = (0..length as u64)
+        .map(|seed| {
+            let seed = mix(seed.wrapping_add(0x9e37_79b9_7f4a_7c15));
+            fingerprints
+                .hashes
+                .iter()
+                .map(|hash| mix(hash ^ seed))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect();
+
+    Some(signature)
+}
+
+// SplitMix64 finalizer, turns one hash into a family of independent permutations
+fn mix(value: u64) -> u64 {
+    let mut value = value;
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+impl LshSettings {
+    /// Splits the signature into bands, so that pairs with the estimated similarity
+    /// around the threshold have about 50% chance to become candidates.
+    /// Pairs above the threshold are found with a rapidly growing probability.
+    /// The signature may be shortened to a multiple of the rows, as divisors of
+    /// the configured length alone are too coarse to follow the threshold.
+    pub fn bands_and_rows(&self) -> (usize, usize) {
+        let signature_length = self.signature_length.max(1);
+        let threshold = self.threshold.clamp(0.01, 0.99);
+
+        (1..=signature_length)
+            .map(|rows| (signature_length / rows, rows))
+            .min_by(|(first_bands, first_rows), (second_bands, second_rows)| {
+                let error = |bands: usize, rows: usize| {
+                    ((1.0 / bands as f64).powf(1.0 / rows as f64) - threshold).abs()
+                };
+                error(*first_bands, *first_rows)
+                    .total_cmp(&error(*second_bands, *second_rows))
+            })
+            .unwrap_or((signature_length, 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::database::DatabaseSettings;
+    use crate::models::submission::{CodeFile, SubmissionMetadata};
+
+    const SORT: &str = "void sort(int *values, int count) {
+    for (int i = 0; i < count; i++) {
+        for (int j = 0; j + 1 < count - i; j++) {
+            if (values[j] > values[j + 1]) {
+                int swap = values[j];
+                values[j] = values[j + 1];
+                values[j + 1] = swap;
+            }
+        }
+    }
+}";
+
+    const GREET: &str = "struct person { char *name; };
+
+static const char *greeting(const struct person *who) {
+    return who->name ? who->name : \"stranger\";
+}
+
+int main(void) {
+    struct person who = { \"Ada\" };
+    printf(\"Hello, %s!\\n\", greeting(&who));
+    return 0;
+}";
+
+    fn submission(student_name: &str, content: &str) -> Submission {
+        Submission::new(
+            SubmissionMetadata {
+                student_name: student_name.to_string(),
+                assignment_title: None,
+            },
+            vec![CodeFile {
+                relative_path: "main.c".to_string(),
+                content: content.to_string(),
+                extension: "c".to_string(),
+            }],
+        )
+    }
+
+    #[test]
+    fn bands_match_the_threshold() {
+        for threshold in [0.1, 0.3, 0.5, 0.8] {
+            let settings = LshSettings {
+                threshold,
+                ..LshSettings::default()
+            };
+            let (bands, rows) = settings.bands_and_rows();
+            assert!(bands * rows <= settings.signature_length);
+
+            let estimate = (1.0 / bands as f64).powf(1.0 / rows as f64);
+            assert!(
+                (estimate - threshold).abs() < 0.1,
+                "{threshold}: {estimate}"
+            );
+        }
+
+        let empty = LshSettings {
+            signature_length: 0,
+            ..LshSettings::default()
+        };
+        assert_eq!(empty.bands_and_rows(), (1, 1));
+    }
+
+    #[test]
+    fn only_similar_submissions_are_candidates() {
+        let settings = DatabaseSettings::default();
+        let context = CheckContext::new(&settings);
+        let submissions = [
+            submission("Ivanov", SORT),
+            submission("Petrenko", GREET),
+            submission("Sydorenko", &SORT.replace("swap", "temporary")),
+            submission("Koval", ""),
+        ];
+
+        assert_eq!(candidate_pairs(&submissions, &context), [(0, 2)]);
+    }
+}
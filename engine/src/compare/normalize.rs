@@ -0,0 +1,184 @@
+use crate::compare::language::{Language, Syntax};
+use crate::compare::token::{self, TokenKind};
+use crate::models::comparison::NormalizationSettings;
+use std::ops::Range;
+
+const IDENTIFIER_PLACEHOLDER: &str = "$id";
+const LITERAL_PLACEHOLDER: &str = "$lit";
+
+/// Builds normalized text of the content, for comparators working with text instead of tokens.
+pub fn normalize_text(
+    content: &str, language: Language, settings: &NormalizationSettings,
+) -> String {
+    let mut normalized = String::with_capacity(content.len());
+    let mut previous_end = 0;
+
+    for token in token::tokenize(content, language, settings) {
+        // Keeping only whitespace from the gap, skipped comments and imports are dropped
+        let gap = content.get(previous_end..token.start).unwrap_or_default();
+        if settings.collapse_whitespace {
+            if normalized.is_empty() {
+                // Leading whitespace is dropped
+            } else if gap.contains('\n') {
+                normalized.push('\n');
+            } else if !gap.is_empty() {
+                normalized.push(' ');
+            }
+        } else {
+            normalized.extend(gap.chars().filter(|character| character.is_whitespace()));
+        }
+
+        let text = content.get(token.start..token.end).unwrap_or_default();
+        match token.kind {
+            TokenKind::Identifier
+                if settings.replace_identifiers && language != Language::Generic =>
+            {
+                normalized.push_str(IDENTIFIER_PLACEHOLDER)
+            },
+            TokenKind::Literal if settings.replace_literals => {
+                normalized.push_str(LITERAL_PLACEHOLDER)
+            },
+            _ => normalized.push_str(text),
+        }
+        previous_end = token.end;
+    }
+
+    normalized
+}
+
+/// Byte ranges of import lines in the content.
+/// Imports with unbalanced brackets continue on the following lines until they are closed.
+pub fn import_ranges(content: &str, syntax: &Syntax) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut open_brackets = 0_i32;
+    let mut line_start = 0;
+
+    for line in content.split_inclusive('\n') {
+        let line_range = line_start..line_start + line.len();
+        line_start += line.len();
+
+        let is_continuation = open_brackets > 0;
+        if !is_continuation && !is_import(line, syntax) {
+            continue;
+        }
+
+        open_brackets += line
+            .chars()
+            .map(|character| match character {
+                '(' | '{' => 1,
+                ')' | '}' => -1,
+                _ => 0,
+            })
+            .sum::<i32>();
+        open_brackets = open_brackets.max(0);
+
+        match ranges.last_mut() {
+            Some(last) if last.end == line_range.start => last.end = line_range.end,
+            _ => ranges.push(line_range),
+        }
+    }
+
+    ranges
+}
+
+fn is_import(line: &str, syntax: &Syntax) -> bool {
+    let line = line.trim();
+
+    // Preprocessor directives may have spaces after '#', like "# include <stdio.h>"
+    let line = match line.strip_prefix('#') {
+        Some(directive) => format!("#{}", directive.trim_start()),
+        None => line.to_string(),
+    };
+
+    syntax.import_prefixes.iter().any(|prefix| {
+        line.strip_prefix(*prefix)
+            .is_some_and(|rest| match *prefix {
+                // C# `using` also starts statements, like `using (reader) {`
+                "using" => is_using_directive(rest),
+                _ => rest.chars().next().is_some_and(|next| {
+                    next.is_whitespace() || matches!(next, '"' | '\'' | '<' | '{' | '*')
+                }),
+            })
+    })
+}
+
+/// Rest of a C# `using X;`, `using static X;` or `using A = X;` directive.
+fn is_using_directive(rest: &str) -> bool {
+    let Some(rest) = rest.trim_end().strip_suffix(';') else {
+        return false;
+    };
+    if !rest.starts_with(char::is_whitespace) {
+        return false;
+    }
+    let rest = rest.trim_start().strip_prefix("static ").unwrap_or(rest);
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name.chars().all(|character| {
+                character.is_alphanumeric()
+                    || character.is_whitespace()
+                    || matches!(character, '_' | '.' | ':' | '<' | '>' | ',')
+            })
+    };
+
+    match rest.split_once('=') {
+        Some((alias, name)) => {
+            let alias = alias.trim();
+            !alias.contains(char::is_whitespace) && is_name(alias) && is_name(name.trim())
+        },
+        None => is_name(rest.trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn imports(content: &str, language: Language) -> Vec<&str> {
+        import_ranges(content, language.syntax())
+            .into_iter()
+            .filter_map(|range| content.get(range))
+            .collect()
+    }
+
+    #[test]
+    fn csharp_using_statement_is_not_an_import() {
+        let content = "using System;\nusing static System.Math;\nusing Io = System.IO;\n\
+            \nclass A {\n    void F() {\n        using (reader) {\n            Read();\n        }\n\
+            \n        using var stream = Open(path);\n    }\n}\n";
+
+        assert_eq!(
+            imports(content, Language::CSharp),
+            ["using System;\nusing static System.Math;\nusing Io = System.IO;\n"]
+        );
+    }
+
+    #[test]
+    fn bracketed_imports_continue_until_closed() {
+        let content =
+            "package main\n\nimport (\n    \"fmt\"\n    \"os\"\n)\n\nfunc main() {}\n";
+        assert_eq!(
+            imports(content, Language::Go),
+            ["package main\n", "import (\n    \"fmt\"\n    \"os\"\n)\n"]
+        );
+
+        let content = "from os import (\n    path,\n)\nimportant = 1\n";
+        assert_eq!(
+            imports(content, Language::Python),
+            ["from os import (\n    path,\n)\n"]
+        );
+    }
+
+    #[test]
+    fn spaced_preprocessor_directive_is_an_import() {
+        let content = "# include <stdio.h>\nint main() {}\n";
+        assert_eq!(imports(content, Language::C), ["# include <stdio.h>\n"]);
+    }
+
+    #[test]
+    fn identifiers_and_literals_are_replaced() {
+        let settings = NormalizationSettings::default();
+        let normalized =
+            normalize_text("int  total = 42; // sum\n", Language::C, &settings);
+        assert_eq!(normalized, "int $id = $lit;");
+    }
+}
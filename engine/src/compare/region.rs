@@ -0,0 +1,91 @@
+use crate::compare::Match;
+use crate::models::submission::CodeFile;
+use serde::{Deserialize, Serialize};
+
+/// Fragment of code found in both submissions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchRegion {
+    pub first: FileRange,
+    pub second: FileRange,
+
+    // Amount of matched tokens, or of syntax nodes for AST matches
+    pub tokens: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileRange {
+    // Relative path of the file in the submission
+    pub file: String,
+
+    // End position is exclusive
+    pub start: Position,
+    pub end: Position,
+
+    // Byte offsets in the file content, end is exclusive
+    pub start_offset: usize,
+    pub end_offset: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Position {
+    // Both are 1-based, column is counted in characters
+    pub line: usize,
+    pub column: usize,
+}
+
+impl MatchRegion {
+    /// Resolves file indexes and byte offsets of the match against the compared files.
+    pub fn new(found: &Match, first: &[CodeFile], second: &[CodeFile]) -> Option<Self> {
+        Some(Self {
+            first: FileRange::new(
+                first.get(found.first.file)?,
+                found.first.start,
+                found.first.end,
+            ),
+            second: FileRange::new(
+                second.get(found.second.file)?,
+                found.second.start,
+                found.second.end,
+            ),
+            tokens: found.length,
+        })
+    }
+
+    pub fn from_matches(
+        matches: &[Match], first: &[CodeFile], second: &[CodeFile],
+    ) -> Vec<Self> {
+        matches
+            .iter()
+            .filter_map(|found| Self::new(found, first, second))
+            .collect()
+    }
+}
+
+impl FileRange {
+    pub fn new(file: &CodeFile, start_offset: usize, end_offset: usize) -> Self {
+        Self {
+            file: file.relative_path.clone(),
+            start: Position::at(&file.content, start_offset),
+            end: Position::at(&file.content, end_offset),
+            start_offset,
+            end_offset,
+        }
+    }
+
+    /// Line numbers covered by the range, for highlighting.
+    pub fn lines(&self) -> std::ops::RangeInclusive<usize> {
+        self.start.line..=self.end.line
+    }
+}
+
+impl Position {
+    pub fn at(content: &str, offset: usize) -> Self {
+        let before = content.get(..offset).unwrap_or(content);
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before.get(line_start..).unwrap_or_default().chars().count() + 1,
+        }
+    }
+}
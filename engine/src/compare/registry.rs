@@ -0,0 +1,60 @@
+use crate::compare::{Comparator, CompareError};
+use crate::models::comparison::Algorithm;
+use crate::models::database::DatabaseSettings;
+use std::sync::Arc;
+
+/// Resolves the comparator selected in the database settings.
+/// Built-in comparators are always available, custom ones must be registered first.
+#[derive(Default, Clone)]
+pub struct ComparatorRegistry {
+    custom: Vec<Arc<dyn Comparator>>,
+}
+
+impl std::fmt::Debug for ComparatorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComparatorRegistry")
+            .field(
+                "custom",
+                &self
+                    .custom
+                    .iter()
+                    .map(|comparator| comparator.name())
+                    .collect::<Vec<&str>>(),
+            )
+            .finish()
+    }
+}
+
+impl ComparatorRegistry {
+    /// Registers a custom comparator. Comparator with the same name is replaced.
+    pub fn register(&mut self, comparator: impl Comparator + 'static) {
+        self.custom
+            .retain(|registered| registered.name() != comparator.name());
+        self.custom.push(Arc::new(comparator));
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.custom
+            .iter()
+            .map(|comparator| comparator.name())
+            .collect()
+    }
+
+    pub fn resolve(
+        &self, settings: &DatabaseSettings,
+    ) -> Result<Arc<dyn Comparator>, CompareError> {
+        let comparison = &settings.comparison;
+        match &comparison.algorithm {
+            Algorithm::Winnowing => Ok(Arc::new(comparison.winnowing)),
+            Algorithm::GreedyStringTiling => Ok(Arc::new(comparison.tiling)),
+            Algorithm::Ast => Ok(Arc::new(comparison.ast)),
+            Algorithm::CompressionDistance => Ok(Arc::new(comparison.compression)),
+            Algorithm::Custom(name) => self
+                .custom
+                .iter()
+                .find(|comparator| comparator.name() == name)
+                .cloned()
+                .ok_or(CompareError::UnknownComparator(name.clone())),
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use crate::compare::context::CheckContext;
+use crate::compare::region::MatchRegion;
+use crate::compare::token::{self, Token};
+use crate::compare::{Comparator, Comparison, Match, Span};
+use crate::models::comparison::NormalizationSettings;
+use crate::models::submission::{CodeFile, Submission};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GreedyStringTiling {
+    // Tiles shorter than this amount of tokens are ignored
+    pub minimum_match_length: usize,
+}
+
+impl Default for GreedyStringTiling {
+    fn default() -> Self {
+        Self {
+            minimum_match_length: 9,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FileToken {
+    token: Token,
+    file: usize,
+}
+
+// Candidate tile: positions of the first tokens in both streams and length
+type Candidate = (usize, usize, usize);
+
+impl Comparator for GreedyStringTiling {
+    fn name(&self) -> &str {
+        "Greedy String Tiling"
+    }
+
+    fn compare(
+        &self, first: &Submission, second: &Submission, context: &CheckContext,
+    ) -> Comparison {
+        self.compare_submissions(
+            first,
+            second,
+            &context.settings.comparison.normalization,
+        )
+    }
+}
+
+impl GreedyStringTiling {
+    pub fn compare_files(
+        &self, first: &CodeFile, second: &CodeFile, settings: &NormalizationSettings,
+    ) -> Comparison {
+        self.compare_file_sets(
+            std::slice::from_ref(first),
+            std::slice::from_ref(second),
+            settings,
+        )
+    }
+
+    pub fn compare_submissions(
+        &self, first: &Submission, second: &Submission, settings: &NormalizationSettings,
+    ) -> Comparison {
+        self.compare_file_sets(&first.files, &second.files, settings)
+    }
+
+    pub fn compare_file_sets(
+        &self, first: &[CodeFile], second: &[CodeFile], settings: &NormalizationSettings,
+    ) -> Comparison {
+        let (tiles, similarity) = self.tile(
+            &Self::stream(first, settings),
+            &Self::stream(second, settings),
+        );
+
+        Comparison {
+            similarity,
+            regions: MatchRegion::from_matches(&tiles, first, second),
+            files: Vec::new(),
+            algorithm: self.name().to_string(),
+        }
+    }
+
+    /// Concatenates token streams of the files, remembering the origin of every token.
+    fn stream(files: &[CodeFile], settings: &NormalizationSettings) -> Vec<FileToken> {
+        files
+            .iter()
+            .enumerate()
+            .flat_map(|(file, code_file)| {
+                token::tokenize_file(code_file, settings)
+                    .into_iter()
+                    .map(move |token| FileToken { token, file })
+            })
+            .collect()
+    }
+
+    /// Returns the tiles and the share of tokens they cover.
+    fn tile(&self, first: &[FileToken], second: &[FileToken]) -> (Vec<Match>, f64) {
+        let minimum_match_length = self.minimum_match_length.max(1);
+
+        let mut first_marked = vec![false; first.len()];
+        let mut second_marked = vec![false; second.len()];
+        let mut tiles = Vec::new();
+
+        // Positions of every token value in the second stream,
+        // so matches are only searched where they can start
+        let mut positions: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, file_token) in second.iter().enumerate() {
+            positions
+                .entry(file_token.token.value)
+                .or_default()
+                .push(index);
+        }
+
+        loop {
+            let mut maximal_match = minimum_match_length;
+            let mut candidates: Vec<Candidate> = Vec::new();
+
+            for (first_start, file_token) in first.iter().enumerate() {
+                if first_marked.get(first_start).copied().unwrap_or(true) {
+                    continue;
+                }
+                let Some(second_starts) = positions.get(&file_token.token.value) else {
+                    continue;
+                };
+
+                for &second_start in second_starts {
+                    let length = Self::match_length(
+                        first,
+                        second,
+                        &first_marked,
+                        &second_marked,
+                        first_start,
+                        second_start,
+                    );
+
+                    if length > maximal_match {
+                        maximal_match = length;
+                        candidates.clear();
+                    }
+                    if length == maximal_match {
+                        candidates.push((first_start, second_start, length));
+                    }
+                }
+            }
+
+            // Marking the longest matches, unless they were occluded by an earlier tile
+            for (first_start, second_start, length) in candidates {
+                let first_range = first_start..first_start + length;
+                let second_range = second_start..second_start + length;
+
+                let is_occluded = first_marked
+                    .get(first_range.clone())
+                    .is_none_or(|marks| marks.contains(&true))
+                    || second_marked
+                        .get(second_range.clone())
+                        .is_none_or(|marks| marks.contains(&true));
+                if is_occluded {
+                    continue;
+                }
+
+                first_marked
+                    .get_mut(first_range.clone())
+                    .into_iter()
+                    .flatten()
+                    .for_each(|mark| *mark = true);
+                second_marked
+                    .get_mut(second_range.clone())
+                    .into_iter()
+                    .flatten()
+                    .for_each(|mark| *mark = true);
+
+                if let (Some(first_span), Some(second_span)) = (
+                    Self::span(first, first_range),
+                    Self::span(second, second_range),
+                ) {
+                    tiles.push(Match {
+                        first: first_span,
+                        second: second_span,
+                        length,
+                    });
+                }
+            }
+
+            if maximal_match == minimum_match_length {
+                break;
+            }
+        }
+
+        let covered: usize = tiles.iter().map(|tile| tile.length).sum();
+        let total = first.len() + second.len();
+        let similarity = if total == 0 {
+            0.0
+        } else {
+            (2 * covered) as f64 / total as f64
+        };
+
+        (tiles, similarity)
+    }
+
+    /// Length of the common unmarked token sequence starting at the given positions.
+    /// Sequences never cross file boundaries.
+    fn match_length(
+        first: &[FileToken], second: &[FileToken], first_marked: &[bool],
+        second_marked: &[bool], first_start: usize, second_start: usize,
+    ) -> usize {
+        let (Some(first_tokens), Some(second_tokens)) =
+            (first.get(first_start..), second.get(second_start..))
+        else {
+            return 0;
+        };
+        let (Some(first_file), Some(second_file)) = (
+            first_tokens.first().map(|file_token| file_token.file),
+            second_tokens.first().map(|file_token| file_token.file),
+        ) else {
+            return 0;
+        };
+
+        first_tokens
+            .iter()
+            .zip(first_marked.iter().skip(first_start))
+            .zip(
+                second_tokens
+                    .iter()
+                    .zip(second_marked.iter().skip(second_start)),
+            )
+            .take_while(|((first_token, first_mark), (second_token, second_mark))| {
+                !**first_mark
+                    && !**second_mark
+                    && first_token.file == first_file
+                    && second_token.file == second_file
+                    && first_token.token.value == second_token.token.value
+            })
+            .count()
+    }
+
+    fn span(stream: &[FileToken], range: std::ops::Range<usize>) -> Option<Span> {
+        let tokens = stream.get(range)?;
+        let first = tokens.first()?;
+        let last = tokens.last()?;
+
+        Some(Span {
+            file: first.file,
+            start: first.token.start,
+            end: last.token.end,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUM: &str = "int sum(int count) {
+    int total = 0;
+    for (int i = 0; i < count; i++) {
+        total += i * i;
+    }
+    return total;
+}
+";
+
+    const MAX: &str = "int max(int first, int second) {
+    if (first > second) {
+        return first;
+    }
+    return second;
+}
+";
+
+    fn file(path: &str, content: &str) -> CodeFile {
+        CodeFile {
+            relative_path: path.to_string(),
+            content: content.to_string(),
+            extension: "c".to_string(),
+        }
+    }
+
+    fn compare(first: &[CodeFile], second: &[CodeFile], minimum: usize) -> Comparison {
+        let tiling = GreedyStringTiling {
+            minimum_match_length: minimum,
+        };
+        tiling.compare_file_sets(first, second, &NormalizationSettings::default())
+    }
+
+    #[test]
+    fn identical_files_are_one_tile() {
+        let comparison = compare(&[file("a.c", SUM)], &[file("b.c", SUM)], 9);
+        assert_eq!(comparison.similarity, 1.0);
+        assert_eq!(comparison.regions.len(), 1);
+    }
+
+    #[test]
+    fn reordered_functions_are_tiled() {
+        let comparison = compare(
+            &[file("a.c", &format!("{SUM}{MAX}"))],
+            &[file("b.c", &format!("{MAX}{SUM}"))],
+            9,
+        );
+        assert_eq!(comparison.similarity, 1.0);
+        assert_eq!(comparison.regions.len(), 2);
+    }
+
+    #[test]
+    fn short_matches_are_ignored() {
+        let comparison = compare(&[file("a.c", SUM)], &[file("b.c", MAX)], 9);
+        assert!(comparison.similarity < 0.5);
+
+        let comparison = compare(&[file("a.c", SUM)], &[file("b.c", SUM)], 1000);
+        assert_eq!(comparison.similarity, 0.0);
+        assert!(comparison.regions.is_empty());
+    }
+
+    #[test]
+    fn tiles_stay_within_files() {
+        let comparison = compare(
+            &[file("sum.c", SUM), file("max.c", MAX)],
+            &[file("all.c", &format!("{SUM}{MAX}"))],
+            9,
+        );
+        assert_eq!(comparison.similarity, 1.0);
+
+        let paths: Vec<&str> = comparison
+            .regions
+            .iter()
+            .map(|region| region.first.file.as_str())
+            .collect();
+        assert_eq!(paths, ["sum.c", "max.c"]);
+    }
+}
@@ -0,0 +1,320 @@
+use crate::compare::language::{Language, Syntax};
+use crate::compare::{hash, normalize};
+use crate::models::comparison::NormalizationSettings;
+use crate::models::submission::CodeFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+
+    // Hash of the normalized token, used for comparison
+    pub value: u64,
+
+    // Byte offsets of the token in the file content
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Identifier,
+    Keyword,
+    Literal,
+    Operator,
+    Comment,
+}
+
+// Multi-character operators, longest first (maximal munch)
+const OPERATORS: &[&str] = &[
+    ">>>=", "<<=", ">>=", ">>>", "...", "**=", "//=", "<=>", "===", "!==", "::", "->",
+    "=>", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=", "*=", "/=", "%=",
+    "&=", "|=", "^=", "<<", ">>", "**", "//", ":=", "..", "?.", "??",
+];
+
+pub fn tokenize_file(file: &CodeFile, settings: &NormalizationSettings) -> Vec<Token> {
+    tokenize(
+        &file.content,
+        Language::from_extension(&file.extension),
+        settings,
+    )
+}
+
+/// Splits the content into tokens according to the language syntax.
+/// Whitespace is always skipped, the rest depends on the normalization settings.
+pub fn tokenize(
+    content: &str, language: Language, settings: &NormalizationSettings,
+) -> Vec<Token> {
+    let syntax = language.syntax();
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    let imports = if settings.ignore_imports {
+        normalize::import_ranges(content, syntax)
+    } else {
+        Vec::new()
+    };
+
+    while let Some(rest) = content.get(position..)
+        && let Some(character) = rest.chars().next()
+    {
+        if character.is_whitespace() {
+            position += character.len_utf8();
+            continue;
+        }
+
+        if let Some(import) = imports.iter().find(|range| range.contains(&position)) {
+            position = import.end;
+            continue;
+        }
+
+        let (kind, length) = if let Some(length) = comment_length(rest, syntax) {
+            if settings.strip_comments {
+                position += length;
+                continue;
+            }
+            (TokenKind::Comment, length)
+        } else if let Some(length) = lifetime_length(rest, syntax) {
+            (TokenKind::Identifier, length)
+        } else if let Some(length) = string_length(rest, syntax) {
+            (TokenKind::Literal, length)
+        } else if is_number_start(rest) {
+            (TokenKind::Literal, number_length(rest))
+        } else if is_word_char(character) {
+            let length = word_length(rest);
+            let word = rest.get(..length).unwrap_or_default();
+            if syntax.keywords.contains(&word) {
+                (TokenKind::Keyword, length)
+            } else {
+                (TokenKind::Identifier, length)
+            }
+        } else {
+            let length = OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(**operator))
+                .map(|operator| operator.len())
+                .unwrap_or(character.len_utf8());
+            (TokenKind::Operator, length)
+        };
+
+        let text = rest.get(..length).unwrap_or_default();
+        tokens.push(Token {
+            kind,
+            value: normalized_value(kind, text, language, settings),
+            start: position,
+            end: position + length,
+        });
+        position += length;
+    }
+
+    tokens
+}
+
+fn normalized_value(
+    kind: TokenKind, text: &str, language: Language, settings: &NormalizationSettings,
+) -> u64 {
+    match kind {
+        // Without a keyword list every word is an identifier,
+        // so generic identifiers keep their text
+        TokenKind::Identifier
+            if settings.replace_identifiers && language != Language::Generic =>
+        {
+            hash::hash_str("<identifier>")
+        },
+        TokenKind::Literal if settings.replace_literals => hash::hash_str("<literal>"),
+        TokenKind::Comment if settings.collapse_whitespace => {
+            hash::hash_str(&text.split_whitespace().collect::<Vec<&str>>().join(" "))
+        },
+        _ => hash::hash_str(text),
+    }
+}
+
+fn comment_length(rest: &str, syntax: &Syntax) -> Option<usize> {
+    if syntax
+        .line_comments
+        .iter()
+        .any(|marker| rest.starts_with(*marker))
+    {
+        return Some(rest.find('\n').unwrap_or(rest.len()));
+    }
+
+    syntax
+        .block_comments
+        .iter()
+        .find(|(opening, _)| rest.starts_with(*opening))
+        .map(|(opening, closing)| {
+            rest.get(opening.len()..)
+                .and_then(|body| body.find(*closing))
+                .map(|end| opening.len() + end + closing.len())
+                .unwrap_or(rest.len())
+        })
+}
+
+fn string_length(rest: &str, syntax: &Syntax) -> Option<usize> {
+    let delimiter = syntax
+        .string_delimiters
+        .iter()
+        .find(|delimiter| rest.starts_with(**delimiter))?;
+    // Only triple quotes and backticks may span several lines
+    let is_multiline = delimiter.len() > 1 || *delimiter == "`";
+
+    let body = rest.get(delimiter.len()..)?;
+    let mut characters = body.char_indices();
+    while let Some((index, character)) = characters.next() {
+        if character == '\\' {
+            characters.next();
+            continue;
+        }
+        if character == '\n' && !is_multiline {
+            // Unterminated literal ends with the line
+            return Some(delimiter.len() + index);
+        }
+        if body.get(index..)?.starts_with(*delimiter) {
+            return Some(delimiter.len() + index + delimiter.len());
+        }
+    }
+
+    Some(rest.len())
+}
+
+fn lifetime_length(rest: &str, syntax: &Syntax) -> Option<usize> {
+    if !syntax.has_lifetimes {
+        return None;
+    }
+
+    let name = rest.strip_prefix('\'')?;
+    let length = word_length(name);
+    if length == 0 || name.get(length..)?.starts_with('\'') {
+        // Character literal, like 'a'
+        return None;
+    }
+
+    Some(1 + length)
+}
+
+fn is_number_start(rest: &str) -> bool {
+    let mut characters = rest.chars();
+    match characters.next() {
+        Some(character) if character.is_ascii_digit() => true,
+        Some('.') => characters.next().is_some_and(|next| next.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+fn number_length(rest: &str) -> usize {
+    let mut length = 0;
+    let mut previous = None;
+    for character in rest.chars() {
+        let is_exponent_sign =
+            matches!(character, '+' | '-') && matches!(previous, Some('e' | 'E'));
+        if !(is_word_char(character) || character == '.' || is_exponent_sign) {
+            break;
+        }
+        length += character.len_utf8();
+        previous = Some(character);
+    }
+
+    length
+}
+
+fn word_length(rest: &str) -> usize {
+    rest.chars()
+        .take_while(|character| is_word_char(*character))
+        .map(char::len_utf8)
+        .sum()
+}
+
+fn is_word_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: &str, language: Language) -> Vec<(&str, TokenKind)> {
+        tokenize(content, language, &NormalizationSettings::default())
+            .into_iter()
+            .filter_map(|token| Some((content.get(token.start..token.end)?, token.kind)))
+            .collect()
+    }
+
+    #[test]
+    fn builtins_are_identifiers() {
+        assert_eq!(
+            kinds("if len(self):", Language::Python),
+            [
+                ("if", TokenKind::Keyword),
+                ("len", TokenKind::Identifier),
+                ("(", TokenKind::Operator),
+                ("self", TokenKind::Identifier),
+                (")", TokenKind::Operator),
+                (":", TokenKind::Operator),
+            ]
+        );
+        assert_eq!(
+            kinds("let x = Some(1);", Language::Rust).get(3),
+            Some(&("Some", TokenKind::Identifier))
+        );
+    }
+
+    #[test]
+    fn operators_are_munched() {
+        let operators: Vec<&str> = kinds("a >>>= b != c->d", Language::Java)
+            .into_iter()
+            .filter(|(_, kind)| *kind == TokenKind::Operator)
+            .map(|(text, _)| text)
+            .collect();
+        assert_eq!(operators, [">>>=", "!=", "->"]);
+    }
+
+    #[test]
+    fn literals_and_comments_are_recognized() {
+        let content = "s = \"\"\"a\n\"b\"\"\" # note\nn = 1.5e-3";
+        let settings = NormalizationSettings {
+            strip_comments: false,
+            ..NormalizationSettings::default()
+        };
+        let kinds: Vec<TokenKind> = tokenize(content, Language::Python, &settings)
+            .into_iter()
+            .map(|token| token.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Identifier,
+                TokenKind::Operator,
+                TokenKind::Literal,
+                TokenKind::Comment,
+                TokenKind::Identifier,
+                TokenKind::Operator,
+                TokenKind::Literal,
+            ]
+        );
+    }
+
+    #[test]
+    fn lifetimes_are_not_characters() {
+        assert_eq!(
+            kinds("&'a str, 'b'", Language::Rust),
+            [
+                ("&", TokenKind::Operator),
+                ("'a", TokenKind::Identifier),
+                ("str", TokenKind::Identifier),
+                (",", TokenKind::Operator),
+                ("'b'", TokenKind::Literal),
+            ]
+        );
+    }
+
+    #[test]
+    fn renamed_identifiers_have_the_same_value() {
+        let values = |content: &str| -> Vec<u64> {
+            tokenize(content, Language::C, &NormalizationSettings::default())
+                .into_iter()
+                .map(|token| token.value)
+                .collect()
+        };
+        assert_eq!(values("int sum = 1;"), values("int total = 2;"));
+        assert_ne!(values("int sum = 1;"), values("long sum = 1;"));
+    }
+}
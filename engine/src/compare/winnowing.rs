@@ -0,0 +1,376 @@
+use crate::compare::context::CheckContext;
+use crate::compare::region::MatchRegion;
+use crate::compare::token::{self, Token};
+use crate::compare::{Comparator, Comparison, Match, Span, hash};
+use crate::models::comparison::NormalizationSettings;
+use crate::models::submission::{CodeFile, Submission};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Winnowing {
+    // Amount of tokens in one k-gram (noise threshold).
+    // Matches shorter than this are never detected.
+    pub kgram_size: usize,
+
+    // Amount of consecutive k-grams in one window.
+    // Any match of at least `kgram_size + window_size - 1` tokens is guaranteed to be detected.
+    pub window_size: usize,
+}
+
+impl Default for Winnowing {
+    fn default() -> Self {
+        Self {
+            kgram_size: 5,
+            window_size: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub hash: u64,
+
+    // Index of the file in the submission
+    pub file: usize,
+
+    // Index of the first k-gram token in the file token stream
+    pub position: usize,
+
+    // Byte offsets of the k-gram in the file content
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionFingerprints {
+    pub fingerprints: Vec<Fingerprint>,
+    pub hashes: HashSet<u64>,
+}
+
+impl SubmissionFingerprints {
+    pub fn new(fingerprints: Vec<Fingerprint>) -> Self {
+        let hashes = fingerprints
+            .iter()
+            .map(|fingerprint| fingerprint.hash)
+            .collect();
+
+        Self {
+            fingerprints,
+            hashes,
+        }
+    }
+
+    /// Dice coefficient of the fingerprint sets, in range 0.0..=1.0.
+    pub fn similarity(&self, other: &Self) -> f64 {
+        let total = self.hashes.len() + other.hashes.len();
+        if total == 0 {
+            return 0.0;
+        }
+
+        let shared = self.hashes.intersection(&other.hashes).count();
+        (2 * shared) as f64 / total as f64
+    }
+}
+
+impl Comparator for Winnowing {
+    fn name(&self) -> &str {
+        "Winnowing"
+    }
+
+    fn compare(
+        &self, first: &Submission, second: &Submission, context: &CheckContext,
+    ) -> Comparison {
+        // Cached fingerprints are valid only for the winnowing settings of the database
+        let (first_fingerprints, second_fingerprints) = if context.winnowing() == self {
+            (
+                context.fingerprint_submission(first),
+                context.fingerprint_submission(second),
+            )
+        } else {
+            let normalization = &context.settings.comparison.normalization;
+            (
+                self.fingerprint_submission(first, normalization),
+                self.fingerprint_submission(second, normalization),
+            )
+        };
+
+        Comparison {
+            similarity: first_fingerprints.similarity(&second_fingerprints),
+            regions: MatchRegion::from_matches(
+                &self.matches(&first_fingerprints, &second_fingerprints),
+                &first.files,
+                &second.files,
+            ),
+            files: Vec::new(),
+            algorithm: self.name().to_string(),
+        }
+    }
+}
+
+impl Winnowing {
+    /// Pairs the shared fingerprints and merges neighbouring ones into longer matches.
+    pub fn matches(
+        &self, first: &SubmissionFingerprints, second: &SubmissionFingerprints,
+    ) -> Vec<Match> {
+        let mut second_by_hash: HashMap<u64, &Fingerprint> = HashMap::new();
+        for fingerprint in &second.fingerprints {
+            second_by_hash
+                .entry(fingerprint.hash)
+                .or_insert(fingerprint);
+        }
+
+        let mut pairs: Vec<(&Fingerprint, &Fingerprint)> = first
+            .fingerprints
+            .iter()
+            .filter_map(|fingerprint| {
+                second_by_hash
+                    .remove(&fingerprint.hash)
+                    .map(|other| (fingerprint, other))
+            })
+            .collect();
+        pairs.sort_by_key(|(first, second)| {
+            (first.file, first.position, second.file, second.position)
+        });
+
+        let kgram_size = self.kgram_size.max(1);
+        let mut merged: Vec<MergedMatch> = Vec::new();
+        for (first, second) in pairs {
+            if let Some(last) = merged.last_mut()
+                && last.continues_with(first, second, self.window_size)
+            {
+                last.extend(first, second, kgram_size);
+                continue;
+            }
+            merged.push(MergedMatch::new(first, second, kgram_size));
+        }
+
+        merged.into_iter().map(|merged| merged.found).collect()
+    }
+
+    pub fn fingerprint_submission(
+        &self, submission: &Submission, settings: &NormalizationSettings,
+    ) -> SubmissionFingerprints {
+        SubmissionFingerprints::new(
+            submission
+                .files
+                .iter()
+                .enumerate()
+                .flat_map(|(index, file)| self.fingerprint_file(file, index, settings))
+                .collect(),
+        )
+    }
+
+    pub fn fingerprint_file(
+        &self, file: &CodeFile, index: usize, settings: &NormalizationSettings,
+    ) -> Vec<Fingerprint> {
+        self.fingerprint_tokens(&token::tokenize_file(file, settings), index)
+    }
+
+    /// Selects fingerprints from the token stream of one file.
+    /// In every window the k-gram with the minimal hash is selected (the rightmost one on ties),
+    /// and the same k-gram is never recorded twice in a row.
+    pub fn fingerprint_tokens(&self, tokens: &[Token], file: usize) -> Vec<Fingerprint> {
+        let kgram_size = self.kgram_size.max(1);
+        let window_size = self.window_size.max(1);
+
+        let kgrams: Vec<Fingerprint> = tokens
+            .windows(kgram_size)
+            .enumerate()
+            .filter_map(|(position, kgram)| {
+                let values: Vec<u64> = kgram.iter().map(|token| token.value).collect();
+                Some(Fingerprint {
+                    hash: hash::hash_sequence(&values),
+                    file,
+                    position,
+                    start: kgram.first()?.start,
+                    end: kgram.last()?.end,
+                })
+            })
+            .collect();
+
+        // Text is shorter than one window, so the best k-gram represents it
+        if kgrams.len() < window_size {
+            return Self::minimal(kgrams.iter().enumerate())
+                .map(|(_, fingerprint)| vec![*fingerprint])
+                .unwrap_or_default();
+        }
+
+        let mut selected: Vec<Fingerprint> = Vec::new();
+        let mut last_position = None;
+        for (offset, window) in kgrams.windows(window_size).enumerate() {
+            if let Some((position, fingerprint)) =
+                Self::minimal(window.iter().enumerate())
+                && last_position != Some(offset + position)
+            {
+                last_position = Some(offset + position);
+                selected.push(*fingerprint);
+            }
+        }
+
+        selected
+    }
+
+    fn minimal<'a>(
+        kgrams: impl Iterator<Item = (usize, &'a Fingerprint)>,
+    ) -> Option<(usize, &'a Fingerprint)> {
+        // `min_by_key` returns the first minimum, but winnowing needs the rightmost one
+        kgrams.fold(None, |best, current| match best {
+            Some((_, fingerprint)) if fingerprint.hash < current.1.hash => best,
+            _ => Some(current),
+        })
+    }
+}
+
+// Match with token positions of both sides, used while merging fingerprints
+struct MergedMatch {
+    found: Match,
+
+    first_start: usize,
+    first_end: usize,
+    second_start: usize,
+}
+
+impl MergedMatch {
+    fn new(first: &Fingerprint, second: &Fingerprint, kgram_size: usize) -> Self {
+        Self {
+            found: Match {
+                first: Span {
+                    file: first.file,
+                    start: first.start,
+                    end: first.end,
+                },
+                second: Span {
+                    file: second.file,
+                    start: second.start,
+                    end: second.end,
+                },
+                length: kgram_size,
+            },
+            first_start: first.position,
+            first_end: first.position + kgram_size,
+            second_start: second.position,
+        }
+    }
+
+    /// Both k-grams follow the match closely (winnowing skips at most a window),
+    /// and they are shifted by the same amount of tokens on both sides.
+    fn continues_with(
+        &self, first: &Fingerprint, second: &Fingerprint, window_size: usize,
+    ) -> bool {
+        self.found.first.file == first.file
+            && self.found.second.file == second.file
+            && first.position >= self.first_start
+            && first.position <= self.first_end + window_size
+            && second.position >= self.second_start
+            && first.position - self.first_start == second.position - self.second_start
+    }
+
+    fn extend(&mut self, first: &Fingerprint, second: &Fingerprint, kgram_size: usize) {
+        self.found.first.end = self.found.first.end.max(first.end);
+        self.found.second.end = self.found.second.end.max(second.end);
+        self.first_end = self.first_end.max(first.position + kgram_size);
+        self.found.length = self.first_end - self.first_start;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compare::token::TokenKind;
+
+    const CODE: &str = "int main() {
+    int sum = 0;
+    for (int i = 0; i < 10; i++) {
+        sum += i * i;
+    }
+    return sum;
+}";
+
+    fn tokens(values: &[u64]) -> Vec<Token> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| Token {
+                kind: TokenKind::Identifier,
+                value: *value,
+                start: index,
+                end: index + 1,
+            })
+            .collect()
+    }
+
+    fn fingerprints(content: &str) -> SubmissionFingerprints {
+        let file = CodeFile {
+            relative_path: "main.c".to_string(),
+            content: content.to_string(),
+            extension: "c".to_string(),
+        };
+        SubmissionFingerprints::new(Winnowing::default().fingerprint_file(
+            &file,
+            0,
+            &NormalizationSettings::default(),
+        ))
+    }
+
+    #[test]
+    fn every_window_has_a_fingerprint() {
+        let winnowing = Winnowing::default();
+        let values: Vec<u64> = (0..40).map(|value| value * 7 % 13).collect();
+        let selected = winnowing.fingerprint_tokens(&tokens(&values), 0);
+
+        let kgrams = values.len() - winnowing.kgram_size + 1;
+        for window in 0..=kgrams - winnowing.window_size {
+            let positions = window..window + winnowing.window_size;
+            assert!(
+                selected
+                    .iter()
+                    .any(|fingerprint| positions.contains(&fingerprint.position))
+            );
+        }
+    }
+
+    #[test]
+    fn repeated_minimum_is_selected_once() {
+        let selected = Winnowing::default().fingerprint_tokens(&tokens(&[1; 20]), 0);
+        let positions: Vec<usize> = selected
+            .iter()
+            .map(|fingerprint| fingerprint.position)
+            .collect();
+
+        let mut unique = positions.clone();
+        unique.dedup();
+        assert_eq!(positions, unique);
+    }
+
+    #[test]
+    fn short_text_has_one_fingerprint() {
+        let winnowing = Winnowing::default();
+        assert_eq!(
+            winnowing
+                .fingerprint_tokens(&tokens(&[1, 2, 3, 4, 5, 6]), 0)
+                .len(),
+            1
+        );
+        assert!(winnowing.fingerprint_tokens(&tokens(&[1, 2]), 0).is_empty());
+    }
+
+    #[test]
+    fn renamed_code_is_identical() {
+        let renamed = CODE.replace("sum", "total").replace("main", "run");
+        assert_eq!(fingerprints(CODE).similarity(&fingerprints(&renamed)), 1.0);
+        assert_eq!(fingerprints(CODE).similarity(&fingerprints("")), 0.0);
+    }
+
+    #[test]
+    fn neighbouring_fingerprints_merge_into_one_match() {
+        let matches =
+            Winnowing::default().matches(&fingerprints(CODE), &fingerprints(CODE));
+        assert_eq!(matches.len(), 1);
+
+        let found = matches.first().map(|found| (found.first, found.second));
+        assert_eq!(found.map(|(first, _)| first.start), Some(0));
+        assert_eq!(found.map(|(first, second)| first == second), Some(true));
+    }
+}
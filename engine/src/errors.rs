@@ -1,3 +1,4 @@
+use crate::compare::CompareError;
 use crate::io::IoError;
 use crate::models::database::DatabaseError;
 use thiserror::Error;
@@ -15,4 +16,7 @@ pub enum LibError {
 
     #[error("Database. {0}")]
     Database(#[from] DatabaseError),
+
+    #[error("Comparison. {0}")]
+    Compare(#[from] CompareError),
 }
@@ -1,6 +1,6 @@
 use crate::errors::LibError;
 use crate::models::database::DatabaseSettings;
-use crate::models::submission::{CodeFile, Submission, SubmissionMetadata};
+use crate::models::submission::{CodeFile, Provenance, Submission, SubmissionMetadata};
 use std::fs::File;
 use std::io::{Seek, SeekFrom};
 use std::path::{Path, PathBuf};
@@ -185,10 +185,11 @@ impl FileLoader {
             return Err(FileError::EmptySubmission);
         }
 
-        Ok(Submission {
-            metadata: submission_metadata,
-            files: code_files,
-        })
+        let mut submission = Submission::new(submission_metadata, code_files);
+        submission.provenance =
+            Some(Provenance::from_archive(path).map_err(FileError::Io)?);
+
+        Ok(submission)
     }
 
     /// Recursively scans a directory on the disk.
@@ -246,10 +247,10 @@ impl FileLoader {
             return Err(FileError::EmptySubmission);
         }
 
-        Ok(Submission {
-            metadata: submission_metadata,
-            files: code_files,
-        })
+        let mut submission = Submission::new(submission_metadata, code_files);
+        submission.provenance = Some(Provenance::from_folder(root_path, &submission));
+
+        Ok(submission)
     }
 
     /// Helper: Checks if the path is valid based on Blacklist and Whitelist settings.
@@ -1,4 +1,6 @@
 // Re-exports
+/// Similarity algorithm interface, implement it to add custom comparators
+pub use compare::Comparator;
 /// Library error type. Critical
 pub use errors::LibError;
 /// Library error type for bad files, can be shown for users
@@ -9,6 +11,7 @@ pub use io::FileLoader;
 pub use models::database::Database;
 
 // Modules
+pub mod compare;
 pub mod errors;
 pub mod io;
 pub mod models;
@@ -1,4 +1,13 @@
+pub mod base_code;
+pub mod collaboration;
+pub mod comparison;
 pub mod database;
 pub mod ignore;
+pub mod migration;
 pub mod pattern;
+pub mod result;
+pub mod retention;
+pub mod review;
+pub mod scope;
 pub mod submission;
+pub mod verdict;
@@ -0,0 +1,33 @@
+use crate::models::scope;
+use crate::models::submission::{CodeFile, Submission, SubmissionMetadata};
+use serde::{Deserialize, Serialize};
+
+/// Template code handed out by the instructor, shared by every submission.
+/// Fragments matching it are excluded before comparison.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaseCode {
+    // Applied only to submissions of this assignment, or to all of them if not set
+    pub assignment_title: Option<String>,
+
+    pub files: Vec<CodeFile>,
+}
+
+// Entry of the base code list stored in the database archive
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct BaseCodeEntry {
+    pub assignment_title: Option<String>,
+    pub files: Vec<String>,
+}
+
+impl BaseCode {
+    pub fn new(submission: Submission, assignment_title: Option<String>) -> Self {
+        Self {
+            assignment_title,
+            files: submission.files,
+        }
+    }
+
+    pub fn applies_to(&self, metadata: &SubmissionMetadata) -> bool {
+        scope::covers(self.assignment_title.as_deref(), metadata)
+    }
+}
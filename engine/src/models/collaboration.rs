@@ -0,0 +1,58 @@
+use crate::models::scope;
+use crate::models::submission::SubmissionMetadata;
+use serde::{Deserialize, Serialize};
+
+/// Students allowed to work together, so similarity of their submissions is expected.
+/// An allowed pair is a team of two.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Team {
+    // Applied only to this assignment, or to all of them if not set
+    pub assignment_title: Option<String>,
+
+    // Student names, as in the submission metadata
+    pub members: Vec<String>,
+}
+
+impl Team {
+    pub fn new(assignment_title: Option<String>, members: Vec<String>) -> Self {
+        Self {
+            assignment_title,
+            members,
+        }
+    }
+
+    pub fn applies_to(&self, metadata: &SubmissionMetadata) -> bool {
+        scope::covers(self.assignment_title.as_deref(), metadata)
+    }
+
+    /// Renames the member, or adds the new name while the old one is still in use.
+    pub fn rename_member(&mut self, old_name: &str, new_name: &str, keep_old: bool) {
+        if !self.members.iter().any(|member| member == old_name) {
+            return;
+        }
+
+        if !keep_old {
+            self.members.retain(|member| member != old_name);
+        }
+        if !self.members.iter().any(|member| member == new_name) {
+            self.members.push(new_name.to_string());
+        }
+    }
+
+    /// Both submissions belong to members of this team, for the same assignment.
+    pub fn contains(
+        &self, first: &SubmissionMetadata, second: &SubmissionMetadata,
+    ) -> bool {
+        first.assignment_title == second.assignment_title
+            && self.applies_to(first)
+            && self.members.contains(&first.student_name)
+            && self.members.contains(&second.student_name)
+    }
+}
+
+/// Submissions of the pair are allowed to match by any of the teams.
+pub fn is_expected(
+    teams: &[Team], first: &SubmissionMetadata, second: &SubmissionMetadata,
+) -> bool {
+    teams.iter().any(|team| team.contains(first, second))
+}
@@ -0,0 +1,117 @@
+use crate::compare::ast::AstComparator;
+use crate::compare::compression::CompressionDistance;
+use crate::compare::tiling::GreedyStringTiling;
+use crate::compare::winnowing::Winnowing;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComparisonSettings {
+    pub algorithm: Algorithm,
+    pub winnowing: Winnowing,
+    pub tiling: GreedyStringTiling,
+    pub ast: AstComparator,
+    pub compression: CompressionDistance,
+    pub normalization: NormalizationSettings,
+    pub lsh: LshSettings,
+    pub aggregation: AggregationSettings,
+}
+
+/// How scores of multi-file submissions are computed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AggregationSettings {
+    pub mode: AggregationMode,
+
+    // Files with fewer tokens (after base code exclusion) are not compared.
+    // 0 keeps every file.
+    pub minimum_file_tokens: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggregationMode {
+    /// All files of the submission are compared as one document
+    #[default]
+    Submission,
+
+    /// Every file is compared with every file of the other submission.
+    /// The score is the similarity of best-matching files, weighted by file size.
+    File,
+}
+
+/// Candidate pre-filtering for large corpora.
+/// When enabled, only pairs found by locality-sensitive hashing are compared.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LshSettings {
+    pub is_enabled: bool,
+
+    // Amount of MinHash values per submission, rounded down to whole bands
+    pub signature_length: usize,
+
+    // Estimated fingerprint similarity (0.0..=1.0) from which pairs become candidates.
+    // Lower values find more pairs (recall), higher values compare fewer pairs (speed).
+    pub threshold: f64,
+}
+
+impl Default for LshSettings {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            signature_length: 128,
+            threshold: 0.3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizationSettings {
+    pub strip_comments: bool,
+
+    // Applies to kept comments and to the text compared by compression distance.
+    // Tokenizer always skips whitespace, so token-based comparators
+    // (Winnowing, GST, AST) are not affected.
+    pub collapse_whitespace: bool,
+
+    // Renamed variables are treated as the same code
+    pub replace_identifiers: bool,
+
+    // Changed numeric and string constants are treated as the same code
+    pub replace_literals: bool,
+
+    // Import, include, using and package lines are skipped
+    pub ignore_imports: bool,
+}
+
+impl Default for NormalizationSettings {
+    fn default() -> Self {
+        Self {
+            strip_comments: true,
+            collapse_whitespace: true,
+            replace_identifiers: true,
+            replace_literals: true,
+            ignore_imports: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Algorithm {
+    /// MOSS-style fingerprinting
+    #[default]
+    Winnowing,
+
+    /// JPlag-style tiling
+    GreedyStringTiling,
+
+    /// Structural comparison of syntax trees (Python, C, C++, Java)
+    Ast,
+
+    /// Normalized compression distance, for languages without a tokenizer.
+    /// Every algorithm, custom ones too, falls back to it for submissions made only of such files.
+    CompressionDistance,
+
+    /// Comparator registered in `ComparatorRegistry` under the given name
+    Custom(String),
+}
@@ -1,10 +1,27 @@
+use crate::compare;
+use crate::compare::cache::FingerprintCache;
+use crate::compare::context::CheckContext;
+use crate::compare::control::CheckControl;
+use crate::compare::registry::ComparatorRegistry;
+use crate::compare::{Reference, VersionComparison};
 use crate::errors::LibError;
-use crate::io::IoError;
+use crate::io::{FileLoader, IoError};
+use crate::models::base_code::{BaseCode, BaseCodeEntry};
+use crate::models::collaboration::Team;
+use crate::models::comparison::ComparisonSettings;
 use crate::models::ignore::IgnoreList;
+use crate::models::migration::{Documents, FORMAT_VERSION};
 use crate::models::pattern::FileNamePattern;
-use crate::models::submission::{CodeFile, Submission, SubmissionMetadata};
+use crate::models::result::{CheckResult, DatabaseOrigin, PairResult};
+use crate::models::retention::ResultRetention;
+use crate::models::review::{Review, ReviewDecision};
+use crate::models::scope::AssignmentScope;
+use crate::models::submission::{
+    CodeFile, Submission, SubmissionEntry, SubmissionMetadata, SubmissionVersion,
+    VersionEntry,
+};
+use crate::models::verdict::Thresholds;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -14,6 +31,12 @@ pub const FILE_EXTENSION: &str = "xai";
 pub const META_FILE_NAME: &str = "meta.json";
 pub const SETTINGS_FILE_NAME: &str = "settings.json";
 pub const SUBMISSIONS_DIR: &str = "submissions";
+pub const SUBMISSIONS_FILE_NAME: &str = "submissions.json";
+pub const BASE_CODE_FILE_NAME: &str = "base_code.json";
+pub const BASE_CODE_DIR: &str = "base";
+pub const RESULTS_DIR: &str = "results";
+pub const REVIEWS_FILE_NAME: &str = "reviews.json";
+pub const FINGERPRINT_CACHE_FILE_NAME: &str = "cache/fingerprints.json";
 
 #[derive(Debug)]
 pub struct Database {
@@ -23,12 +46,57 @@ pub struct Database {
     pub meta: DatabaseMetadata,
     pub settings: DatabaseSettings,
     pub submissions: Vec<Submission>,
+    pub base_code: Vec<BaseCode>,
+
+    // Ordered from the oldest
+    pub results: Vec<CheckResult>,
+
+    // Ordered from the oldest, the latest review of a pair is the current one
+    pub reviews: Vec<Review>,
+
+    pub fingerprint_cache: FingerprintCache,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct DatabaseSettings {
     pub file_name_pattern: FileNamePattern,
     pub ignore_list: IgnoreList,
+
+    #[serde(default)]
+    pub comparison: ComparisonSettings,
+
+    #[serde(default)]
+    pub scope: AssignmentScope,
+
+    // Teams and allowed pairs, their matches are marked as expected
+    #[serde(default)]
+    pub teams: Vec<Team>,
+
+    #[serde(default)]
+    pub thresholds: Thresholds,
+
+    #[serde(default)]
+    pub retention: ResultRetention,
+}
+
+/// Imported submission that was not added, because the database already has it.
+#[derive(Debug)]
+pub struct Duplicate {
+    pub submission: Submission,
+
+    // Id of the submission already in the database
+    pub existing_id: String,
+
+    pub kind: DuplicateKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKind {
+    /// Same files were already imported, for any student
+    SameContent,
+
+    /// Student already has a submission for the assignment, it may be a resubmission
+    SameStudent,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,6 +104,10 @@ pub struct DatabaseMetadata {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+
+    // Version of the archive format, see `migration`. Archives without it are version 0
+    #[serde(default)]
+    pub format_version: u32,
 }
 
 impl DatabaseMetadata {
@@ -44,6 +116,7 @@ impl DatabaseMetadata {
             id: uuid::Uuid::new_v4().to_string(),
             name,
             description,
+            format_version: FORMAT_VERSION,
         }
     }
 }
@@ -59,7 +132,324 @@ impl Database {
             meta: DatabaseMetadata::new(name, description),
             settings,
             submissions: vec![],
+            base_code: vec![],
+            results: vec![],
+            reviews: vec![],
+            fingerprint_cache: FingerprintCache::default(),
+        }
+    }
+
+    /// Compares the submissions and stores the result in the database.
+    /// The result is available through `latest_result`.
+    /// Only new or changed submissions are compared, if the settings are the same.
+    /// Older results are removed as configured in `ResultRetention`.
+    ///
+    /// Submissions of the reference databases (e.g. previous semesters, loaded with `load`)
+    /// are compared with the submissions of this database. References are not modified.
+    pub fn check(
+        &mut self, references: &[Database], registry: &ComparatorRegistry,
+        control: &CheckControl,
+    ) -> Result<(), LibError> {
+        let references: Vec<Reference> = references
+            .iter()
+            .map(|database| Reference {
+                origin: DatabaseOrigin::from(&database.meta),
+                submissions: &database.submissions,
+            })
+            .collect();
+
+        let cache = std::mem::take(&mut self.fingerprint_cache);
+        let context = CheckContext::with_cache(&self.settings, cache);
+
+        let result = compare::check(
+            &self.submissions,
+            &self.base_code,
+            &references,
+            registry,
+            &context,
+            self.results.last(),
+            control,
+        );
+        self.fingerprint_cache = context.into_cache();
+
+        let mut result = result?;
+        if !self.settings.retention.keep_clear_regions {
+            result.discard_clear_regions();
+        }
+        self.results.push(result);
+
+        // Results are ordered from the oldest
+        let excess = self.settings.retention.excess(self.results.len());
+        self.results.drain(..excess);
+        self.is_dirty = true;
+
+        Ok(())
+    }
+
+    pub fn submission(&self, id: &str) -> Option<&Submission> {
+        self.submissions
+            .iter()
+            .find(|submission| submission.id == id)
+    }
+
+    /// Adds submissions imported by the loader, leaving its bad files for the caller.
+    /// Duplicates of existing submissions (and of each other) are not added
+    /// and returned instead, resubmissions can be passed to `replace_submission`.
+    pub fn add_submissions(&mut self, loader: &mut FileLoader) -> Vec<Duplicate> {
+        let mut duplicates = Vec::new();
+        // Content hashes of the existing submissions, in the same order
+        let mut content_hashes: Vec<String> = self
+            .submissions
+            .iter()
+            .map(Submission::content_hash)
+            .collect();
+
+        for submission in std::mem::take(&mut loader.submissions) {
+            let content_hash = submission.content_hash();
+            let existing = self.submissions.iter().zip(&content_hashes).find_map(
+                |(existing, existing_hash)| {
+                    if *existing_hash == content_hash {
+                        Some((existing, DuplicateKind::SameContent))
+                    } else if existing.metadata == submission.metadata {
+                        Some((existing, DuplicateKind::SameStudent))
+                    } else {
+                        None
+                    }
+                },
+            );
+
+            match existing {
+                Some((existing, kind)) => duplicates.push(Duplicate {
+                    existing_id: existing.id.clone(),
+                    submission,
+                    kind,
+                }),
+                None => {
+                    self.submissions.push(submission);
+                    content_hashes.push(content_hash);
+                    self.is_dirty = true;
+                },
+            }
         }
+
+        duplicates
+    }
+
+    pub fn remove_submission(&mut self, id: &str) -> Result<Submission, DatabaseError> {
+        let index = self.submission_index(id)?;
+        self.is_dirty = true;
+
+        Ok(self.submissions.remove(index))
+    }
+
+    /// Renames the student of the submission, teams applying to it are renamed too.
+    pub fn rename_student(
+        &mut self, id: &str, student_name: String,
+    ) -> Result<(), DatabaseError> {
+        let old_metadata = self
+            .submission(id)
+            .map(|submission| submission.metadata.clone())
+            .ok_or(DatabaseError::SubmissionNotFound(id.to_string()))?;
+        self.update_metadata(id, |metadata| {
+            metadata.student_name = student_name.clone()
+        })?;
+
+        for team in &mut self.settings.teams {
+            if !team.applies_to(&old_metadata) {
+                continue;
+            }
+            // Other submissions of the student may still be covered by the team
+            let is_old_name_used = self.submissions.iter().any(|submission| {
+                submission.metadata.student_name == old_metadata.student_name
+                    && team.applies_to(&submission.metadata)
+            });
+            team.rename_member(
+                &old_metadata.student_name,
+                &student_name,
+                is_old_name_used,
+            );
+        }
+        self.reclassify();
+
+        Ok(())
+    }
+
+    /// Moves the submission to another assignment.
+    /// Teams are declared per assignment, so they are not moved with it.
+    pub fn set_assignment_title(
+        &mut self, id: &str, assignment_title: Option<String>,
+    ) -> Result<(), DatabaseError> {
+        self.update_metadata(id, |metadata| metadata.assignment_title = assignment_title)
+    }
+
+    /// Adds files of the resubmission as the latest version of the submission,
+    /// which participates in checks. Previous versions are kept in its history.
+    /// Returns the number of the new version.
+    pub fn replace_submission(
+        &mut self, id: &str, resubmission: Submission,
+    ) -> Result<u32, DatabaseError> {
+        let submission = self.submission_mut(id)?;
+        let version = submission.add_version(resubmission.files, resubmission.provenance);
+        self.is_dirty = true;
+
+        Ok(version)
+    }
+
+    /// Selects the version of the submission participating in checks.
+    pub fn select_version(
+        &mut self, id: &str, version: u32,
+    ) -> Result<(), DatabaseError> {
+        let submission = self.submission_mut(id)?;
+        if !submission.select_version(version) {
+            return Err(DatabaseError::VersionNotFound {
+                id: id.to_string(),
+                version,
+            });
+        }
+        self.is_dirty = true;
+
+        Ok(())
+    }
+
+    /// Compares versions of the submission with each other, e.g. to see what was changed.
+    pub fn compare_versions(
+        &self, id: &str, registry: &ComparatorRegistry, control: &CheckControl,
+    ) -> Result<Vec<VersionComparison>, LibError> {
+        let submission = self
+            .submission(id)
+            .ok_or(DatabaseError::SubmissionNotFound(id.to_string()))?;
+        let context = CheckContext::new(&self.settings);
+
+        let comparisons = compare::compare_versions(
+            submission,
+            &self.base_code,
+            registry,
+            &context,
+            control,
+        )?;
+        Ok(comparisons)
+    }
+
+    fn submission_mut(&mut self, id: &str) -> Result<&mut Submission, DatabaseError> {
+        self.submissions
+            .iter_mut()
+            .find(|submission| submission.id == id)
+            .ok_or(DatabaseError::SubmissionNotFound(id.to_string()))
+    }
+
+    fn submission_index(&self, id: &str) -> Result<usize, DatabaseError> {
+        self.submissions
+            .iter()
+            .position(|submission| submission.id == id)
+            .ok_or(DatabaseError::SubmissionNotFound(id.to_string()))
+    }
+
+    /// Changes metadata of the submission, unless another one already has the same.
+    fn update_metadata(
+        &mut self, id: &str, update: impl FnOnce(&mut SubmissionMetadata),
+    ) -> Result<(), DatabaseError> {
+        let index = self.submission_index(id)?;
+        let mut metadata = self
+            .submissions
+            .get(index)
+            .map(|submission| submission.metadata.clone())
+            .ok_or(DatabaseError::SubmissionNotFound(id.to_string()))?;
+        update(&mut metadata);
+
+        if self
+            .submissions
+            .iter()
+            .any(|submission| submission.id != id && submission.metadata == metadata)
+        {
+            return Err(DatabaseError::DuplicateSubmission {
+                student_name: metadata.student_name,
+                assignment_title: metadata.assignment_title.unwrap_or_default(),
+            });
+        }
+
+        // Results and reviews refer to the submission by id, only displayed metadata changes
+        for result in &mut self.results {
+            result.set_metadata(id, &metadata);
+        }
+        for review in &mut self.reviews {
+            review.set_metadata(id, &metadata);
+        }
+        if let Some(submission) = self.submissions.get_mut(index) {
+            submission.metadata = metadata;
+        }
+        self.reclassify();
+
+        Ok(())
+    }
+
+    pub fn remove_result(&mut self, id: &str) -> Result<CheckResult, DatabaseError> {
+        let index = self
+            .results
+            .iter()
+            .position(|result| result.id == id)
+            .ok_or(DatabaseError::ResultNotFound(id.to_string()))?;
+        self.is_dirty = true;
+
+        Ok(self.results.remove(index))
+    }
+
+    pub fn latest_result(&self) -> Option<&CheckResult> {
+        self.results.last()
+    }
+
+    /// Declares a team (or an allowed pair) and marks its pairs in the latest result.
+    pub fn add_team(&mut self, team: Team) {
+        self.settings.teams.push(team);
+        self.reclassify();
+    }
+
+    /// Changes the thresholds and classifies pairs of the latest result again.
+    pub fn set_thresholds(&mut self, thresholds: Thresholds) {
+        self.settings.thresholds = thresholds;
+        self.reclassify();
+    }
+
+    fn reclassify(&mut self) {
+        if let Some(result) = self.results.last_mut() {
+            result.classify(&self.settings);
+        }
+        self.is_dirty = true;
+    }
+
+    /// Records the decision of the reviewer about the pair.
+    pub fn review(
+        &mut self, pair: &PairResult, reviewer: String, decision: ReviewDecision,
+        note: String,
+    ) {
+        self.reviews
+            .push(Review::new(pair, reviewer, decision, note));
+        self.is_dirty = true;
+    }
+
+    /// Every review of the pair, from the oldest.
+    pub fn reviews_of<'a>(
+        &'a self, pair: &'a PairResult,
+    ) -> impl Iterator<Item = &'a Review> {
+        self.reviews
+            .iter()
+            .filter(move |review| review.concerns(pair))
+    }
+
+    /// Current decision about the pair, if it was reviewed.
+    pub fn latest_review(&self, pair: &PairResult) -> Option<&Review> {
+        self.reviews
+            .iter()
+            .rev()
+            .find(|review| review.concerns(pair))
+    }
+
+    /// Attaches base code to the whole database, or only to the given assignment.
+    pub fn attach_base_code(
+        &mut self, submission: Submission, assignment_title: Option<String>,
+    ) {
+        self.base_code
+            .push(BaseCode::new(submission, assignment_title));
+        self.is_dirty = true;
     }
 
     pub fn save(&mut self) -> Result<(), LibError> {
@@ -88,26 +478,98 @@ impl Database {
         zip.write_all(settings_json.as_bytes())
             .map_err(IoError::Write)?;
 
-        // Submissions
+        // Submissions. Files are stored by submission id and version,
+        // the manifest keeps their metadata
+        let mut submission_entries = Vec::new();
         for submission in &self.submissions {
-            // Forming the path manually using '/'
-            let mut path_parts = vec![
-                SUBMISSIONS_DIR.to_string(),
-                submission.metadata.student_name.clone(),
-            ];
-            if let Some(title) = &submission.metadata.assignment_title {
-                path_parts.push(title.clone());
+            let current = Self::write_version(
+                &mut zip,
+                options,
+                &submission.id,
+                SubmissionVersion {
+                    version: submission.version,
+                    submitted_at: submission.submitted_at,
+                    files: submission.files.clone(),
+                    provenance: submission.provenance.clone(),
+                },
+            )?;
+            let mut history = Vec::new();
+            for version in &submission.history {
+                history.push(Self::write_version(
+                    &mut zip,
+                    options,
+                    &submission.id,
+                    version.clone(),
+                )?);
             }
-            let directory_path = path_parts.join("/");
 
-            for code_file in &submission.files {
-                let file_path = format!("{}/{}", directory_path, code_file.relative_path);
+            submission_entries.push(SubmissionEntry {
+                id: submission.id.clone(),
+                metadata: submission.metadata.clone(),
+                current,
+                history,
+            });
+        }
+        zip.start_file(SUBMISSIONS_FILE_NAME, options)
+            .map_err(LibError::Zip)?;
+        let submissions_json =
+            serde_json::to_string_pretty(&submission_entries).map_err(LibError::Json)?;
+        zip.write_all(submissions_json.as_bytes())
+            .map_err(IoError::Write)?;
+
+        // Base code. Files are stored by index, the list keeps their scope
+        let mut base_code_entries = Vec::new();
+        for (index, base_code) in self.base_code.iter().enumerate() {
+            for code_file in &base_code.files {
+                let file_path =
+                    format!("{}/{}/{}", BASE_CODE_DIR, index, code_file.relative_path);
                 zip.start_file(file_path, options).map_err(LibError::Zip)?;
                 zip.write_all(code_file.content.as_bytes())
                     .map_err(IoError::Write)?;
             }
+
+            base_code_entries.push(BaseCodeEntry {
+                assignment_title: base_code.assignment_title.clone(),
+                files: base_code
+                    .files
+                    .iter()
+                    .map(|code_file| code_file.relative_path.clone())
+                    .collect(),
+            });
+        }
+        zip.start_file(BASE_CODE_FILE_NAME, options)
+            .map_err(LibError::Zip)?;
+        let base_code_json =
+            serde_json::to_string_pretty(&base_code_entries).map_err(LibError::Json)?;
+        zip.write_all(base_code_json.as_bytes())
+            .map_err(IoError::Write)?;
+
+        // Check results
+        for result in &self.results {
+            let file_path = format!("{}/{}.json", RESULTS_DIR, result.id);
+            zip.start_file(file_path, options).map_err(LibError::Zip)?;
+            let result_json =
+                serde_json::to_string_pretty(result).map_err(LibError::Json)?;
+            zip.write_all(result_json.as_bytes())
+                .map_err(IoError::Write)?;
         }
 
+        // Reviews
+        zip.start_file(REVIEWS_FILE_NAME, options)
+            .map_err(LibError::Zip)?;
+        let reviews_json =
+            serde_json::to_string_pretty(&self.reviews).map_err(LibError::Json)?;
+        zip.write_all(reviews_json.as_bytes())
+            .map_err(IoError::Write)?;
+
+        // Fingerprint cache
+        zip.start_file(FINGERPRINT_CACHE_FILE_NAME, options)
+            .map_err(LibError::Zip)?;
+        let cache_json =
+            serde_json::to_string(&self.fingerprint_cache).map_err(LibError::Json)?;
+        zip.write_all(cache_json.as_bytes())
+            .map_err(IoError::Write)?;
+
         zip.finish().map_err(LibError::Zip)?;
 
         // Safe file replacement (.tmp -> original)
@@ -126,87 +588,178 @@ impl Database {
         let file = std::fs::File::open(path).map_err(IoError::Open)?;
         let mut archive = zip::ZipArchive::new(file).map_err(LibError::Zip)?;
 
-        // Reading Metadata
-        let meta: DatabaseMetadata = {
-            let mut file = archive
-                .by_name(META_FILE_NAME)
-                .map_err(|_| DatabaseError::MissingMetadata)?;
-            let mut content = String::new();
-            file.read_to_string(&mut content).map_err(IoError::Read)?;
-            serde_json::from_str(&content).map_err(LibError::Json)?
+        // Reading JSON documents. They are migrated to the current format before parsing
+        let result_names: Vec<String> = archive
+            .file_names()
+            .filter(|name| {
+                name.starts_with(&format!("{}/", RESULTS_DIR)) && name.ends_with(".json")
+            })
+            .map(|name| name.to_string())
+            .collect();
+        let mut documents = Documents {
+            meta: Self::read_json(&mut archive, META_FILE_NAME)?
+                .ok_or(DatabaseError::MissingMetadata)?,
+            settings: Self::read_json(&mut archive, SETTINGS_FILE_NAME)?,
+            base_code: Self::read_json(&mut archive, BASE_CODE_FILE_NAME)?,
+            results: Vec::new(),
+            reviews: Self::read_json(&mut archive, REVIEWS_FILE_NAME)?,
+            submissions: Self::read_json(&mut archive, SUBMISSIONS_FILE_NAME)?,
+            file_names: archive.file_names().map(|name| name.to_string()).collect(),
         };
+        for name in &result_names {
+            if let Some(result) = Self::read_json(&mut archive, name)? {
+                documents.results.push(result);
+            }
+        }
+        let is_migrated = documents.migrate()?;
 
-        // Reading Settings
-        let settings: DatabaseSettings = {
-            match archive.by_name(SETTINGS_FILE_NAME) {
+        let meta: DatabaseMetadata =
+            serde_json::from_value(documents.meta).map_err(LibError::Json)?;
+        let settings: DatabaseSettings = match documents.settings {
+            Some(settings) => serde_json::from_value(settings).map_err(LibError::Json)?,
+            None => DatabaseSettings::default(),
+        };
+
+        // Reading Base code
+        let base_code_entries: Vec<BaseCodeEntry> = match documents.base_code {
+            Some(entries) => serde_json::from_value(entries).map_err(LibError::Json)?,
+            None => vec![],
+        };
+        let mut base_code = Vec::new();
+        for (index, entry) in base_code_entries.into_iter().enumerate() {
+            let mut files = Vec::new();
+            for relative_path in entry.files {
+                let path_str = format!("{}/{}/{}", BASE_CODE_DIR, index, relative_path);
+                let mut file = archive.by_name(&path_str).map_err(LibError::Zip)?;
+                let mut content = String::new();
+                file.read_to_string(&mut content).map_err(IoError::Read)?;
+
+                files.push(CodeFile {
+                    extension: Path::new(&relative_path)
+                        .extension()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    relative_path,
+                    content,
+                });
+            }
+
+            base_code.push(BaseCode {
+                assignment_title: entry.assignment_title,
+                files,
+            });
+        }
+
+        // Reading Check results
+        let mut results: Vec<CheckResult> = documents
+            .results
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()
+            .map_err(LibError::Json)?;
+        results.sort_by_key(|result| result.timestamp);
+
+        // Reading Reviews
+        let reviews: Vec<Review> = match documents.reviews {
+            Some(reviews) => serde_json::from_value(reviews).map_err(LibError::Json)?,
+            None => vec![],
+        };
+
+        // Reading Fingerprint cache. It's safe to lose, so broken cache is dropped
+        let fingerprint_cache: FingerprintCache = {
+            match archive.by_name(FINGERPRINT_CACHE_FILE_NAME) {
                 Ok(mut file) => {
                     let mut content = String::new();
                     file.read_to_string(&mut content).map_err(IoError::Read)?;
-                    serde_json::from_str(&content).map_err(LibError::Json)?
+                    serde_json::from_str(&content).unwrap_or_else(|error| {
+                        log::warn!(
+                            "Fingerprint cache is broken and will be dropped: {}",
+                            error
+                        );
+                        FingerprintCache::default()
+                    })
                 },
-                Err(_) => DatabaseSettings::default(),
+                Err(_) => FingerprintCache::default(),
             }
         };
 
-        // Reading Submissions
-        // Grouping files by (student, assignment)
-        let mut grouped_files: HashMap<(String, Option<String>), Vec<CodeFile>> =
-            HashMap::new();
+        // Reading Submissions, the manifest is the source of truth
+        let submission_entries: Vec<SubmissionEntry> = match documents.submissions {
+            Some(entries) => serde_json::from_value(entries).map_err(LibError::Json)?,
+            None => vec![],
+        };
+        let mut submissions = Vec::new();
+        for entry in submission_entries {
+            let mut history = Vec::new();
+            for version in entry.history {
+                history.push(SubmissionVersion {
+                    files: Self::read_files(&mut archive, &version)?,
+                    version: version.version,
+                    submitted_at: version.submitted_at,
+                    provenance: version.provenance,
+                });
+            }
 
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i).map_err(LibError::Zip)?;
+            submissions.push(Submission {
+                id: entry.id,
+                metadata: entry.metadata,
+                files: Self::read_files(&mut archive, &entry.current)?,
+                provenance: entry.current.provenance,
+                version: entry.current.version,
+                submitted_at: entry.current.submitted_at,
+                history,
+            });
+        }
 
-            // Skipping directories
-            if file.is_dir() {
-                continue;
-            }
+        Ok(Self {
+            file_path: path.to_path_buf(),
+            // Migrated database is written in the current format on the next save
+            is_dirty: is_migrated,
+            meta,
+            settings,
+            submissions,
+            base_code,
+            results,
+            reviews,
+            fingerprint_cache,
+        })
+    }
 
-            // Normalize paths to use '/' as separator
-            let path_str = file.name().replace('\\', "/");
-            let parts: Vec<&str> = path_str.split('/').collect();
+    fn write_version<W: Write + std::io::Seek>(
+        zip: &mut zip::ZipWriter<W>, options: SimpleFileOptions, id: &str,
+        version: SubmissionVersion,
+    ) -> Result<VersionEntry, LibError> {
+        // Forming the path manually using '/'
+        let directory_path = format!("{}/{}/{}", SUBMISSIONS_DIR, id, version.version);
+
+        for code_file in &version.files {
+            let file_path = format!("{}/{}", directory_path, code_file.relative_path);
+            zip.start_file(file_path, options).map_err(LibError::Zip)?;
+            zip.write_all(code_file.content.as_bytes())
+                .map_err(IoError::Write)?;
+        }
 
-            // Ignore files not following the pattern
-            if parts.len() < 4
-                || (parts.len() < 3
-                    && matches!(settings.file_name_pattern, FileNamePattern::StudentOnly))
-            {
-                continue;
-            }
+        Ok(VersionEntry {
+            version: version.version,
+            submitted_at: version.submitted_at,
+            directory: directory_path,
+            files: version
+                .files
+                .into_iter()
+                .map(|code_file| code_file.relative_path)
+                .collect(),
+            provenance: version.provenance,
+        })
+    }
 
-            // Extracting student, assignment (if applicable), and relative path based on pattern
-            let (student, assignment, relative_path) = match &settings.file_name_pattern {
-                FileNamePattern::StudentTask { .. } => {
-                    match &parts[..] {
-                        [SUBMISSIONS_DIR, student, assignment, rest @ ..] => (
-                            student.to_string(),
-                            Some(assignment.to_string()),
-                            rest.iter().map(|s| s.to_string()).collect::<Vec<String>>(),
-                        ),
-                        _ => continue, // Files outside submissions directory are ignored
-                    }
-                },
-                FileNamePattern::TaskStudent { .. } => {
-                    match &parts[..] {
-                        [SUBMISSIONS_DIR, assignment, student, rest @ ..] => (
-                            student.to_string(),
-                            Some(assignment.to_string()),
-                            rest.iter().map(|s| s.to_string()).collect(),
-                        ),
-                        _ => continue, // Files outside submissions directory are ignored
-                    }
-                },
-                FileNamePattern::StudentOnly => {
-                    match &parts[..] {
-                        [SUBMISSIONS_DIR, student, rest @ ..] => (
-                            student.to_string(),
-                            None,
-                            rest.iter().map(|s| s.to_string()).collect(),
-                        ),
-                        _ => continue, // Files outside submissions directory are ignored
-                    }
-                },
-            };
-            let relative_path = relative_path.join("/");
+    fn read_files<R: Read + std::io::Seek>(
+        archive: &mut zip::ZipArchive<R>, entry: &VersionEntry,
+    ) -> Result<Vec<CodeFile>, LibError> {
+        let mut files = Vec::new();
+        for relative_path in &entry.files {
+            let path_str = format!("{}/{}", entry.directory, relative_path);
+            let mut file = archive.by_name(&path_str).map_err(LibError::Zip)?;
 
             // Reading content (safe for UTF-8)
             let mut buffer = Vec::new();
@@ -223,41 +776,33 @@ impl Database {
                 },
             };
 
-            let code_file = CodeFile {
-                relative_path,
-                content,
-                extension: Path::new(&path_str)
+            files.push(CodeFile {
+                extension: Path::new(relative_path)
                     .extension()
                     .and_then(|s| s.to_str())
                     .unwrap_or("")
                     .to_string(),
-            };
-
-            grouped_files
-                .entry((student, assignment))
-                .or_default()
-                .push(code_file);
+                relative_path: relative_path.clone(),
+                content,
+            });
         }
 
-        // Converting grouped files into submissions
-        let submissions: Vec<Submission> = grouped_files
-            .into_iter()
-            .map(|((student_name, assignment_title), files)| Submission {
-                metadata: SubmissionMetadata {
-                    student_name,
-                    assignment_title,
-                },
-                files,
-            })
-            .collect();
+        Ok(files)
+    }
 
-        Ok(Self {
-            file_path: path.to_path_buf(),
-            is_dirty: false,
-            meta,
-            settings,
-            submissions,
-        })
+    fn read_json<R: Read + std::io::Seek>(
+        archive: &mut zip::ZipArchive<R>, name: &str,
+    ) -> Result<Option<serde_json::Value>, LibError> {
+        match archive.by_name(name) {
+            Ok(mut file) => {
+                let mut content = String::new();
+                file.read_to_string(&mut content).map_err(IoError::Read)?;
+                Ok(Some(
+                    serde_json::from_str(&content).map_err(LibError::Json)?,
+                ))
+            },
+            Err(_) => Ok(None),
+        }
     }
 }
 
@@ -268,4 +813,34 @@ pub enum DatabaseError {
 
     #[error("Database is missing required metadata.")]
     MissingMetadata,
+
+    #[error(
+        "Database format version {found} is newer than supported ({supported}). Update the application."
+    )]
+    UnsupportedFormatVersion { found: u32, supported: u32 },
+
+    #[error("No migration from database format version {0}.")]
+    MissingMigration(u32),
+
+    #[error("Submission {0} is not found.")]
+    SubmissionNotFound(String),
+
+    #[error(
+        "Thresholds must be ordered as 0 <= suspicious ({suspicious}) <= high ({high}) <= 1."
+    )]
+    InvalidThresholds { suspicious: f64, high: f64 },
+
+    #[error("Check result {0} is not found.")]
+    ResultNotFound(String),
+
+    #[error("Submission {id} has no version {version}.")]
+    VersionNotFound { id: String, version: u32 },
+
+    #[error(
+        "Student \"{student_name}\" already has a submission \"{assignment_title}\"."
+    )]
+    DuplicateSubmission {
+        student_name: String,
+        assignment_title: String,
+    },
 }
@@ -0,0 +1,133 @@
+use crate::models::database::{DatabaseError, SUBMISSIONS_DIR};
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Version of the database archive format written by this build.
+/// Increase it together with adding a migration from the previous version.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// JSON documents of the archive, before they are deserialized into models.
+/// Code files are stored as is, so they don't need migrations.
+#[derive(Debug)]
+pub(crate) struct Documents {
+    pub meta: Value,
+    pub settings: Option<Value>,
+    pub base_code: Option<Value>,
+    pub results: Vec<Value>,
+    pub reviews: Option<Value>,
+    pub submissions: Option<Value>,
+
+    // Paths of every entry in the archive
+    pub file_names: Vec<String>,
+}
+
+// Migration from the version equal to its index to the next one
+type Migration = fn(&mut Documents);
+
+const MIGRATIONS: &[Migration] = &[
+    // 0 -> 1. Archives without a version, written before the submission manifest.
+    // Base code, results and reviews didn't exist, new settings have defaults.
+    build_submission_manifest,
+];
+
+impl Documents {
+    /// Version stored in the metadata, archives created before versioning have none.
+    pub fn format_version(&self) -> u32 {
+        self.meta
+            .get("format_version")
+            .and_then(Value::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            .unwrap_or(0)
+    }
+
+    /// Brings the documents to the current format version.
+    /// Returns true if anything was migrated, so the database must be saved again.
+    pub fn migrate(&mut self) -> Result<bool, DatabaseError> {
+        let version = self.format_version();
+        if version > FORMAT_VERSION {
+            return Err(DatabaseError::UnsupportedFormatVersion {
+                found: version,
+                supported: FORMAT_VERSION,
+            });
+        }
+
+        let migrations = MIGRATIONS
+            .get(version as usize..FORMAT_VERSION as usize)
+            .ok_or(DatabaseError::MissingMigration(version))?;
+        for (index, migration) in migrations.iter().enumerate() {
+            log::info!(
+                "Migrating database format from version {} to {}",
+                version as usize + index,
+                version as usize + index + 1
+            );
+            migration(self);
+        }
+
+        if let Some(meta) = self.meta.as_object_mut() {
+            meta.insert("format_version".to_string(), Value::from(FORMAT_VERSION));
+        }
+
+        Ok(version < FORMAT_VERSION)
+    }
+}
+
+/// Builds the manifest from paths written before it existed:
+/// `submissions/<student>/<assignment>/...`, or `submissions/<student>/...`
+/// for databases without assignments. Every submission becomes its first version,
+/// the import time is unknown, so the migration time is used.
+fn build_submission_manifest(documents: &mut Documents) {
+    if documents.submissions.is_some() {
+        return;
+    }
+
+    let is_student_only = documents
+        .settings
+        .as_ref()
+        .and_then(|settings| settings.get("file_name_pattern"))
+        .and_then(Value::as_str)
+        == Some("StudentOnly");
+    // Amount of directories holding the metadata
+    let depth = if is_student_only { 1 } else { 2 };
+
+    // Ordered by the directories, so the submissions keep a stable order
+    let mut directories: BTreeMap<Vec<String>, Vec<String>> = BTreeMap::new();
+    for name in &documents.file_names {
+        let name = name.replace('\\', "/");
+        if name.ends_with('/') {
+            continue;
+        }
+
+        let parts: Vec<&str> = name.split('/').collect();
+        if let Some((&root, rest)) = parts.split_first()
+            && root == SUBMISSIONS_DIR
+            && rest.len() > depth
+            && let Some((owner, path)) = rest.split_at_checked(depth)
+        {
+            directories
+                .entry(owner.iter().map(|part| part.to_string()).collect())
+                .or_default()
+                .push(path.join("/"));
+        }
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let entries: Vec<Value> = directories
+        .into_iter()
+        .map(|(owner, files)| {
+            json!({
+                "id": uuid::Uuid::new_v4().to_string(),
+                "metadata": {
+                    "student_name": owner.first(),
+                    "assignment_title": owner.get(1),
+                },
+                "version": 1,
+                "submitted_at": now,
+                "directory": format!("{}/{}", SUBMISSIONS_DIR, owner.join("/")),
+                "files": files,
+                "history": [],
+            })
+        })
+        .collect();
+    documents.submissions = Some(Value::Array(entries));
+}
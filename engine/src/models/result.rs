@@ -0,0 +1,342 @@
+use crate::compare::aggregation::FilePair;
+use crate::compare::region::MatchRegion;
+use crate::compare::{PairComparison, hash};
+use crate::models::collaboration;
+use crate::models::comparison::{Algorithm, ComparisonSettings};
+use crate::models::database::{DatabaseMetadata, DatabaseSettings};
+use crate::models::submission::{Submission, SubmissionMetadata};
+use crate::models::verdict::Verdict;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Outcome of one check run over the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub algorithm: Algorithm,
+
+    // Stable hash of the comparison settings used for the run.
+    // Results with different hashes are not comparable.
+    pub settings_hash: String,
+
+    // Checked and reference submissions with hashes of their compared content
+    #[serde(default)]
+    pub submissions: Vec<SubmissionDigest>,
+
+    pub pairs: Vec<PairResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionDigest {
+    // Id of the submission in its database, the metadata is kept for display
+    #[serde(default)]
+    pub submission_id: String,
+
+    pub metadata: SubmissionMetadata,
+
+    // Version participating in the check, 0 if it wasn't recorded
+    #[serde(default)]
+    pub version: u32,
+
+    pub content_hash: String,
+
+    // Set for submissions of reference databases
+    #[serde(default)]
+    pub origin: Option<DatabaseOrigin>,
+}
+
+/// Lookups of a previous result, built once per check.
+#[derive(Debug)]
+pub struct UnchangedPairs<'a> {
+    // Submission id, origin and content hash of every compared submission
+    digests: HashSet<(&'a str, Option<&'a DatabaseOrigin>, &'a str)>,
+
+    pairs: HashMap<PairKey<'a>, &'a PairResult>,
+}
+
+// Origin of the second submission and ids of both
+type PairKey<'a> = (Option<&'a DatabaseOrigin>, &'a str, &'a str);
+
+/// Database the reference submission comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DatabaseOrigin {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairResult {
+    // Ids of the compared submissions, the metadata is kept for display
+    #[serde(default)]
+    pub first_id: String,
+    #[serde(default)]
+    pub second_id: String,
+
+    pub first: SubmissionMetadata,
+    pub second: SubmissionMetadata,
+
+    // Compared versions of the submissions, 0 if they weren't recorded
+    #[serde(default)]
+    pub first_version: u32,
+    #[serde(default)]
+    pub second_version: u32,
+
+    // Database of the second submission, if it's a cross-database match
+    #[serde(default)]
+    pub origin: Option<DatabaseOrigin>,
+
+    // Similarity in range 0.0..=1.0
+    pub similarity: f64,
+
+    // Comparator that scored the pair, compression distance for files without a tokenizer
+    #[serde(default)]
+    pub algorithm: String,
+
+    pub regions: Vec<MatchRegion>,
+
+    // Regions were removed by the retention, the pair is compared again once it's flagged
+    #[serde(default)]
+    pub regions_discarded: bool,
+
+    // Best-matching file pairs, filled in the file aggregation mode
+    #[serde(default)]
+    pub files: Vec<FilePair>,
+
+    // Students of the pair are allowed to collaborate (see `Team`)
+    #[serde(default)]
+    pub is_expected: bool,
+
+    #[serde(default)]
+    pub verdict: Verdict,
+}
+
+impl CheckResult {
+    /// Builds the result from new comparisons (indexes refer to the digests)
+    /// and pairs reused from the previous result.
+    pub fn new(
+        submissions: Vec<SubmissionDigest>, comparisons: Vec<PairComparison>,
+        reused: Vec<PairResult>, settings: &ComparisonSettings,
+    ) -> Self {
+        let mut pairs: Vec<PairResult> = comparisons
+            .into_iter()
+            .filter_map(|pair| {
+                let first = submissions.get(pair.first)?;
+                let second = submissions.get(pair.second)?;
+                Some(PairResult {
+                    first_id: first.submission_id.clone(),
+                    second_id: second.submission_id.clone(),
+                    first: first.metadata.clone(),
+                    second: second.metadata.clone(),
+                    first_version: first.version,
+                    second_version: second.version,
+                    origin: second.origin.clone(),
+                    similarity: pair.comparison.similarity,
+                    algorithm: pair.comparison.algorithm,
+                    regions: pair.comparison.regions,
+                    regions_discarded: false,
+                    files: pair.comparison.files,
+                    is_expected: false,
+                    verdict: Verdict::default(),
+                })
+            })
+            .collect();
+        pairs.extend(reused);
+
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            algorithm: settings.algorithm.clone(),
+            settings_hash: Self::settings_hash(settings),
+            submissions,
+            pairs,
+        }
+    }
+
+    /// Pairs of this result can be reused by a check with the given settings.
+    pub fn is_compatible(&self, settings: &ComparisonSettings) -> bool {
+        self.settings_hash == Self::settings_hash(settings)
+    }
+
+    /// Indexes submissions and pairs of this result, to find the unchanged ones.
+    pub fn unchanged_pairs(&self) -> UnchangedPairs<'_> {
+        UnchangedPairs {
+            digests: self
+                .submissions
+                .iter()
+                .map(|digest| {
+                    (
+                        digest.submission_id.as_str(),
+                        digest.origin.as_ref(),
+                        digest.content_hash.as_str(),
+                    )
+                })
+                .collect(),
+            pairs: self.pairs.iter().fold(HashMap::new(), |mut pairs, pair| {
+                let key = pair_key(pair.origin.as_ref(), &pair.first_id, &pair.second_id);
+                pairs.entry(key).or_insert(pair);
+                pairs
+            }),
+        }
+    }
+
+    pub fn settings_hash(settings: &ComparisonSettings) -> String {
+        // Serialized form covers every setting, including the ones added later
+        let serialized = serde_json::to_string(settings).unwrap_or_default();
+        format!("{:016x}", hash::hash_str(&serialized))
+    }
+
+    /// Matches between submissions of the checked database.
+    pub fn in_course_pairs(&self) -> impl Iterator<Item = &PairResult> {
+        self.pairs.iter().filter(|pair| pair.origin.is_none())
+    }
+
+    /// Matches with submissions of reference databases.
+    pub fn cross_database_pairs(&self) -> impl Iterator<Item = &PairResult> {
+        self.pairs.iter().filter(|pair| pair.origin.is_some())
+    }
+
+    /// Pairs ordered from the most similar.
+    pub fn ranked(&self) -> Vec<&PairResult> {
+        let mut pairs: Vec<&PairResult> = self.pairs.iter().collect();
+        pairs.sort_by(|first, second| second.similarity.total_cmp(&first.similarity));
+        pairs
+    }
+
+    /// Suspicious and high pairs from the most similar, expected pairs are left out.
+    pub fn flagged(&self) -> Vec<&PairResult> {
+        self.ranked()
+            .into_iter()
+            .filter(|pair| !pair.is_expected && pair.verdict != Verdict::Clear)
+            .collect()
+    }
+
+    /// Shows the new name or assignment of the submission in digests and pairs of the result.
+    pub fn set_metadata(&mut self, submission_id: &str, metadata: &SubmissionMetadata) {
+        for digest in &mut self.submissions {
+            if digest.origin.is_none() && digest.submission_id == submission_id {
+                digest.metadata = metadata.clone();
+            }
+        }
+        for pair in &mut self.pairs {
+            pair.set_metadata(submission_id, metadata);
+        }
+    }
+
+    /// Removes regions of pairs below the suspicious threshold, their similarity is kept.
+    pub fn discard_clear_regions(&mut self) {
+        for pair in &mut self.pairs {
+            if pair.verdict == Verdict::Clear && !pair.regions.is_empty() {
+                pair.regions = Vec::new();
+                pair.regions_discarded = true;
+            }
+        }
+    }
+
+    /// Applies the thresholds and marks pairs of team members as expected.
+    /// Cross-database pairs are never expected.
+    pub fn classify(&mut self, settings: &DatabaseSettings) {
+        for pair in &mut self.pairs {
+            pair.verdict = settings.thresholds.classify(pair.similarity);
+            pair.is_expected = pair.origin.is_none()
+                && collaboration::is_expected(&settings.teams, &pair.first, &pair.second);
+        }
+    }
+}
+
+impl<'a> UnchangedPairs<'a> {
+    /// Finds the pair, if both submissions have the same content as in the result.
+    pub fn find(
+        &self, first: &SubmissionDigest, second: &SubmissionDigest,
+    ) -> Option<&'a PairResult> {
+        let is_unchanged = |digest: &SubmissionDigest| {
+            self.digests.contains(&(
+                digest.submission_id.as_str(),
+                digest.origin.as_ref(),
+                digest.content_hash.as_str(),
+            ))
+        };
+        if !is_unchanged(first) || !is_unchanged(second) {
+            return None;
+        }
+
+        let key = pair_key(
+            second.origin.as_ref(),
+            &first.submission_id,
+            &second.submission_id,
+        );
+        self.pairs.get(&key).copied()
+    }
+}
+
+impl PairResult {
+    /// The pair compares these submissions, in any order for pairs within one database.
+    pub fn is_between(&self, first_id: &str, second_id: &str) -> bool {
+        (self.first_id == first_id && self.second_id == second_id)
+            || (self.origin.is_none()
+                && self.first_id == second_id
+                && self.second_id == first_id)
+    }
+
+    /// Shows the new metadata on the side of the pair holding the submission.
+    /// A reference submission keeps its metadata, it belongs to another database.
+    pub fn set_metadata(&mut self, submission_id: &str, metadata: &SubmissionMetadata) {
+        if self.first_id == submission_id {
+            self.first = metadata.clone();
+        }
+        // The second submission may come from a reference database
+        if self.origin.is_none() && self.second_id == submission_id {
+            self.second = metadata.clone();
+        }
+    }
+
+    /// Copy of the pair for the given submissions, with their current metadata and versions.
+    pub fn reused(&self, first: &SubmissionDigest, second: &SubmissionDigest) -> Self {
+        let (first, second) = if self.first_id == first.submission_id {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        Self {
+            first: first.metadata.clone(),
+            second: second.metadata.clone(),
+            first_version: first.version,
+            second_version: second.version,
+            ..self.clone()
+        }
+    }
+}
+
+impl SubmissionDigest {
+    pub fn new(submission: &Submission, origin: Option<DatabaseOrigin>) -> Self {
+        Self {
+            submission_id: submission.id.clone(),
+            metadata: submission.metadata.clone(),
+            version: submission.version,
+            content_hash: submission.content_hash(),
+            origin,
+        }
+    }
+}
+
+impl From<&DatabaseMetadata> for DatabaseOrigin {
+    fn from(metadata: &DatabaseMetadata) -> Self {
+        Self {
+            id: metadata.id.clone(),
+            name: metadata.name.clone(),
+        }
+    }
+}
+
+// Key of the pair in either order of its submissions, except for cross-database pairs
+fn pair_key<'a>(
+    origin: Option<&'a DatabaseOrigin>, first_id: &'a str, second_id: &'a str,
+) -> PairKey<'a> {
+    if origin.is_none() && second_id < first_id {
+        (origin, second_id, first_id)
+    } else {
+        (origin, first_id, second_id)
+    }
+}
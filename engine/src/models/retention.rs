@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// How many check results the database keeps, and how much of them.
+/// Everything is kept by default. Every result is rewritten on save and parsed on load,
+/// so large databases can opt into pruning after checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResultRetention {
+    // Older results are removed after a check, 0 keeps all of them
+    pub max_results: usize,
+
+    // Regions of clear pairs are rarely looked at and take most of the space.
+    // Without them, a clear pair flagged after changing thresholds has no regions
+    // until the next check.
+    pub keep_clear_regions: bool,
+}
+
+impl Default for ResultRetention {
+    fn default() -> Self {
+        Self {
+            max_results: 0,
+            keep_clear_regions: true,
+        }
+    }
+}
+
+impl ResultRetention {
+    /// Amount of the oldest results to remove from the given amount.
+    pub fn excess(&self, results: usize) -> usize {
+        if self.max_results == 0 {
+            0
+        } else {
+            results.saturating_sub(self.max_results)
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::models::result::{DatabaseOrigin, PairResult};
+use crate::models::submission::SubmissionMetadata;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Human decision about a flagged pair.
+/// Bound to the submissions of the pair instead of a check result, so it survives re-checks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Review {
+    // Ids of the reviewed submissions, the metadata is kept for display
+    #[serde(default)]
+    pub first_id: String,
+    #[serde(default)]
+    pub second_id: String,
+
+    pub first: SubmissionMetadata,
+    pub second: SubmissionMetadata,
+
+    // Database of the second submission, if it's a cross-database match
+    #[serde(default)]
+    pub origin: Option<DatabaseOrigin>,
+
+    pub reviewer: String,
+    pub decision: ReviewDecision,
+    pub note: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Verdict of the reviewer, unlike the `Verdict` given by thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewDecision {
+    /// Plagiarism is confirmed
+    Confirmed,
+
+    /// Similarity is legitimate or accidental
+    Dismissed,
+}
+
+impl Review {
+    pub fn new(
+        pair: &PairResult, reviewer: String, decision: ReviewDecision, note: String,
+    ) -> Self {
+        Self {
+            first_id: pair.first_id.clone(),
+            second_id: pair.second_id.clone(),
+            first: pair.first.clone(),
+            second: pair.second.clone(),
+            origin: pair.origin.clone(),
+            reviewer,
+            decision,
+            note,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Keeps the names shown in the review current, it stays attached to the pair by ids.
+    pub fn set_metadata(&mut self, submission_id: &str, metadata: &SubmissionMetadata) {
+        if self.first_id == submission_id {
+            self.first = metadata.clone();
+        }
+        if self.origin.is_none() && self.second_id == submission_id {
+            self.second = metadata.clone();
+        }
+    }
+
+    /// The review is about the same submissions as the pair.
+    pub fn concerns(&self, pair: &PairResult) -> bool {
+        self.origin == pair.origin && pair.is_between(&self.first_id, &self.second_id)
+    }
+}
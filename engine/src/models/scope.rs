@@ -0,0 +1,47 @@
+use crate::models::submission::SubmissionMetadata;
+use serde::{Deserialize, Serialize};
+
+/// Which submissions are compared with each other, by their assignment titles.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssignmentScope {
+    /// Only submissions of the same assignment are compared
+    #[default]
+    SameAssignment,
+
+    /// Every submission is compared with every other one
+    AllAssignments,
+
+    /// Submissions of the selected assignments are compared with each other,
+    /// also across these assignments. The rest are not compared.
+    Selected(Vec<String>),
+}
+
+impl AssignmentScope {
+    pub fn includes(&self, metadata: &SubmissionMetadata) -> bool {
+        match self {
+            Self::SameAssignment | Self::AllAssignments => true,
+            Self::Selected(assignments) => metadata
+                .assignment_title
+                .as_ref()
+                .is_some_and(|title| assignments.contains(title)),
+        }
+    }
+
+    /// Submissions of the pair are compared within this scope.
+    pub fn allows(
+        &self, first: &SubmissionMetadata, second: &SubmissionMetadata,
+    ) -> bool {
+        match self {
+            Self::SameAssignment => first.assignment_title == second.assignment_title,
+            Self::AllAssignments => true,
+            Self::Selected(_) => self.includes(first) && self.includes(second),
+        }
+    }
+}
+
+/// The submission is of the assignment an item is limited to.
+/// Items without an assignment apply to every submission.
+pub fn covers(assignment_title: Option<&str>, metadata: &SubmissionMetadata) -> bool {
+    assignment_title
+        .is_none_or(|title| metadata.assignment_title.as_deref() == Some(title))
+}
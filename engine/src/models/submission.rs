@@ -1,12 +1,82 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Submission {
+    // Stable UUID, kept in the database manifest
+    pub id: String,
+
     pub metadata: SubmissionMetadata,
+
+    // Files of the version participating in checks, the latest one by default
+    pub files: Vec<CodeFile>,
+
+    // Not known for submissions imported before it was recorded
+    pub provenance: Option<Provenance>,
+
+    // Number of the version above, starting from 1
+    pub version: u32,
+    pub submitted_at: DateTime<Utc>,
+
+    // Other versions of the same student and assignment, ordered by number
+    pub history: Vec<SubmissionVersion>,
+}
+
+/// Version of the submission not participating in checks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionVersion {
+    pub version: u32,
+    pub submitted_at: DateTime<Utc>,
     pub files: Vec<CodeFile>,
+    pub provenance: Option<Provenance>,
 }
 
+/// Origin of the imported submission, to trace it back to the original file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    // Original archive or folder
+    pub source_path: String,
+
+    pub imported_at: DateTime<Utc>,
+
+    // Archive size, or total size of the imported files for folders
+    pub source_size: u64,
+
+    // SHA-256 of the archive, or the submission content hash for folders
+    pub source_hash: String,
+}
+
+// Entry of the submission manifest stored in the database archive
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SubmissionEntry {
+    pub id: String,
+    pub metadata: SubmissionMetadata,
+
+    // Version participating in checks
+    #[serde(flatten)]
+    pub current: VersionEntry,
+
+    pub history: Vec<VersionEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct VersionEntry {
+    pub version: u32,
+    pub submitted_at: DateTime<Utc>,
+
+    // Directory of the files in the archive
+    pub directory: String,
+
+    // Relative paths of the files
+    pub files: Vec<String>,
+
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SubmissionMetadata {
     // For example: "John-Doe"
     pub student_name: String,
@@ -21,3 +91,143 @@ pub struct CodeFile {
     pub content: String,
     pub extension: String,
 }
+
+impl Submission {
+    pub fn new(metadata: SubmissionMetadata, files: Vec<CodeFile>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            metadata,
+            files,
+            provenance: None,
+            version: 1,
+            submitted_at: Utc::now(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn latest_version(&self) -> u32 {
+        self.history
+            .iter()
+            .map(|version| version.version)
+            .fold(self.version, u32::max)
+    }
+
+    /// Adds the resubmitted files as the latest version, which participates in checks.
+    /// Returns the number of the new version.
+    pub fn add_version(
+        &mut self, files: Vec<CodeFile>, provenance: Option<Provenance>,
+    ) -> u32 {
+        let version = self.latest_version() + 1;
+        let previous = SubmissionVersion {
+            version: self.version,
+            submitted_at: self.submitted_at,
+            files: std::mem::replace(&mut self.files, files),
+            provenance: std::mem::replace(&mut self.provenance, provenance),
+        };
+        self.history.push(previous);
+        self.history.sort_by_key(|version| version.version);
+
+        self.version = version;
+        self.submitted_at = Utc::now();
+        version
+    }
+
+    /// Makes the version participate in checks. Returns false if there's no such version.
+    pub fn select_version(&mut self, version: u32) -> bool {
+        let Some(index) = self
+            .history
+            .iter()
+            .position(|selected| selected.version == version)
+        else {
+            return version == self.version;
+        };
+
+        if let Some(selected) = self.history.get_mut(index) {
+            std::mem::swap(&mut selected.version, &mut self.version);
+            std::mem::swap(&mut selected.submitted_at, &mut self.submitted_at);
+            std::mem::swap(&mut selected.files, &mut self.files);
+            std::mem::swap(&mut selected.provenance, &mut self.provenance);
+        }
+        self.history.sort_by_key(|version| version.version);
+        true
+    }
+
+    /// Every version as a separate submission with the same id, ordered by number.
+    pub fn versions(&self) -> Vec<Submission> {
+        let mut versions: Vec<Submission> = self
+            .history
+            .iter()
+            .map(|version| Submission {
+                id: self.id.clone(),
+                metadata: self.metadata.clone(),
+                files: version.files.clone(),
+                provenance: version.provenance.clone(),
+                version: version.version,
+                submitted_at: version.submitted_at,
+                history: Vec::new(),
+            })
+            .collect();
+        versions.push(Submission {
+            id: self.id.clone(),
+            metadata: self.metadata.clone(),
+            files: self.files.clone(),
+            provenance: self.provenance.clone(),
+            version: self.version,
+            submitted_at: self.submitted_at,
+            history: Vec::new(),
+        });
+        versions.sort_by_key(|version| version.version);
+        versions
+    }
+
+    /// SHA-256 of every file path and content, independent of the file order.
+    pub fn content_hash(&self) -> String {
+        let mut files: Vec<&CodeFile> = self.files.iter().collect();
+        files.sort_by(|first, second| first.relative_path.cmp(&second.relative_path));
+
+        let mut hasher = Sha256::new();
+        for file in files {
+            hasher.update(file.relative_path.as_bytes());
+            hasher.update([0]);
+            hasher.update(file.content_hash().as_bytes());
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+impl CodeFile {
+    /// SHA-256 of the file content.
+    pub fn content_hash(&self) -> String {
+        format!("{:x}", Sha256::digest(self.content.as_bytes()))
+    }
+}
+
+impl Provenance {
+    /// Hashes the archive file as it is on the disk.
+    pub fn from_archive(path: &Path) -> std::io::Result<Self> {
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Sha256::new();
+        let source_size = std::io::copy(&mut file, &mut hasher)?;
+
+        Ok(Self {
+            source_path: path.to_string_lossy().to_string(),
+            imported_at: Utc::now(),
+            source_size,
+            source_hash: format!("{:x}", hasher.finalize()),
+        })
+    }
+
+    /// Folders have no single file, so the imported content is described instead.
+    pub fn from_folder(path: &Path, submission: &Submission) -> Self {
+        Self {
+            source_path: path.to_string_lossy().to_string(),
+            imported_at: Utc::now(),
+            source_size: submission
+                .files
+                .iter()
+                .map(|file| file.content.len() as u64)
+                .sum(),
+            source_hash: submission.content_hash(),
+        }
+    }
+}
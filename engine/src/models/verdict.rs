@@ -0,0 +1,111 @@
+use crate::models::database::DatabaseError;
+use serde::{Deserialize, Serialize};
+
+/// Similarity thresholds of the database, ordered: 0.0 <= suspicious <= high <= 1.0.
+/// Only ordered thresholds can be constructed or loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "StoredThresholds")]
+pub struct Thresholds {
+    suspicious: f64,
+    high: f64,
+}
+
+// Thresholds as written in the settings, before they are checked
+#[derive(Deserialize)]
+#[serde(default)]
+struct StoredThresholds {
+    suspicious: f64,
+    high: f64,
+}
+
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum Verdict {
+    /// Below the suspicious threshold
+    #[default]
+    Clear,
+
+    Suspicious,
+    High,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            suspicious: 0.4,
+            high: 0.7,
+        }
+    }
+}
+
+impl Default for StoredThresholds {
+    fn default() -> Self {
+        let thresholds = Thresholds::default();
+        Self {
+            suspicious: thresholds.suspicious,
+            high: thresholds.high,
+        }
+    }
+}
+
+impl TryFrom<StoredThresholds> for Thresholds {
+    type Error = DatabaseError;
+
+    fn try_from(stored: StoredThresholds) -> Result<Self, Self::Error> {
+        Self::new(stored.suspicious, stored.high)
+    }
+}
+
+impl Thresholds {
+    pub fn new(suspicious: f64, high: f64) -> Result<Self, DatabaseError> {
+        if (0.0..=high).contains(&suspicious) && high <= 1.0 {
+            Ok(Self { suspicious, high })
+        } else {
+            Err(DatabaseError::InvalidThresholds { suspicious, high })
+        }
+    }
+
+    pub fn suspicious(&self) -> f64 {
+        self.suspicious
+    }
+
+    pub fn high(&self) -> f64 {
+        self.high
+    }
+
+    pub fn classify(&self, similarity: f64) -> Verdict {
+        if similarity >= self.high {
+            Verdict::High
+        } else if similarity >= self.suspicious {
+            Verdict::Suspicious
+        } else {
+            Verdict::Clear
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unordered_thresholds_are_rejected() {
+        assert!(Thresholds::new(0.4, 0.7).is_ok());
+        assert!(Thresholds::new(0.7, 0.4).is_err());
+        assert!(Thresholds::new(-0.1, 0.7).is_err());
+        assert!(Thresholds::new(0.4, 1.1).is_err());
+        assert!(Thresholds::new(f64::NAN, 0.7).is_err());
+    }
+
+    #[test]
+    fn stored_thresholds_are_checked() -> Result<(), serde_json::Error> {
+        let thresholds: Thresholds = serde_json::from_str(r#"{ "high": 0.9 }"#)?;
+        assert_eq!(thresholds.suspicious(), 0.4);
+        assert_eq!(thresholds.high(), 0.9);
+
+        let stored = r#"{ "suspicious": 0.8, "high": 0.5 }"#;
+        assert!(serde_json::from_str::<Thresholds>(stored).is_err());
+        Ok(())
+    }
+}
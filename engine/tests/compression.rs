@@ -0,0 +1,199 @@
+use engine::Database;
+use engine::compare::context::CheckContext;
+use engine::compare::control::CheckControl;
+use engine::compare::registry::ComparatorRegistry;
+use engine::compare::{Comparator, Comparison};
+use engine::models::comparison::{AggregationMode, Algorithm};
+use engine::models::database::DatabaseSettings;
+use engine::models::result::PairResult;
+use engine::models::submission::{CodeFile, Submission, SubmissionMetadata};
+use engine::models::verdict::Verdict;
+use std::error::Error;
+
+const COMPRESSION: &str = "Normalized Compression Distance";
+
+const STUDENTS_SQL: &str = "CREATE TABLE students (
+    id INTEGER PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    group_id INTEGER REFERENCES groups(id)
+);
+
+SELECT s.name, AVG(g.grade) AS average
+FROM students s
+JOIN grades g ON g.student_id = s.id
+GROUP BY s.name
+HAVING AVG(g.grade) > 75
+ORDER BY average DESC;";
+
+const ORDERS_SQL: &str =
+    "INSERT INTO orders (customer, total, placed) VALUES ('Acme', 120.50, NOW());
+
+WITH monthly AS (
+    SELECT date_trunc('month', placed) AS month, SUM(total) AS revenue
+    FROM orders
+    GROUP BY 1
+)
+SELECT month, revenue, revenue - LAG(revenue) OVER (ORDER BY month) AS growth
+FROM monthly
+WHERE revenue IS NOT NULL;";
+
+const SUM_C: &str = "int main() {
+    int sum = 0;
+    for (int i = 0; i < 10; i++) {
+        sum += i * i;
+    }
+    printf(\"%d\", sum);
+    return sum;
+}";
+
+/// Comparator that finds everything similar, to tell whether it was used.
+struct Everything;
+
+impl Comparator for Everything {
+    fn name(&self) -> &str {
+        "Everything"
+    }
+
+    fn compare(&self, _: &Submission, _: &Submission, _: &CheckContext) -> Comparison {
+        Comparison {
+            similarity: 1.0,
+            ..Comparison::default()
+        }
+    }
+}
+
+fn submission(student_name: &str, files: &[(&str, &str)]) -> Submission {
+    Submission::new(
+        SubmissionMetadata {
+            student_name: student_name.to_string(),
+            assignment_title: Some("Lab1".to_string()),
+        },
+        files
+            .iter()
+            .map(|(path, content)| CodeFile {
+                relative_path: path.to_string(),
+                content: content.to_string(),
+                extension: path.rsplit('.').next().unwrap_or_default().to_string(),
+            })
+            .collect(),
+    )
+}
+
+fn checked_pair(
+    settings: DatabaseSettings, registry: &ComparatorRegistry, first: Submission,
+    second: Submission,
+) -> Result<PairResult, Box<dyn Error>> {
+    let mut database =
+        Database::new("Course".to_string(), None, settings, "course.xai".into());
+    database.submissions = vec![first, second];
+    database.check(&[], registry, &CheckControl::default())?;
+
+    database
+        .latest_result()
+        .and_then(|result| result.pairs.first())
+        .cloned()
+        .ok_or_else(|| "check result has no pairs".into())
+}
+
+fn check(first: Submission, second: Submission) -> Result<PairResult, Box<dyn Error>> {
+    checked_pair(
+        DatabaseSettings::default(),
+        &ComparatorRegistry::default(),
+        first,
+        second,
+    )
+}
+
+#[test]
+fn unrelated_generic_pair_is_clear() -> Result<(), Box<dyn Error>> {
+    let pair = check(
+        submission("Ivanov", &[("lab.sql", STUDENTS_SQL)]),
+        submission("Petrenko", &[("lab.sql", ORDERS_SQL)]),
+    )?;
+
+    assert_eq!(pair.algorithm, COMPRESSION);
+    assert!(pair.similarity < 0.1, "similarity {}", pair.similarity);
+    assert_eq!(pair.verdict, Verdict::Clear);
+    Ok(())
+}
+
+#[test]
+fn identical_generic_pair_is_high() -> Result<(), Box<dyn Error>> {
+    let pair = check(
+        submission("Ivanov", &[("lab.sql", STUDENTS_SQL)]),
+        submission("Petrenko", &[("lab.sql", STUDENTS_SQL)]),
+    )?;
+
+    assert_eq!(pair.algorithm, COMPRESSION);
+    assert_eq!(pair.similarity, 1.0);
+    assert_eq!(pair.verdict, Verdict::High);
+    Ok(())
+}
+
+#[test]
+fn mixed_language_pair_is_compared_by_compression() -> Result<(), Box<dyn Error>> {
+    for algorithm in [
+        Algorithm::Winnowing,
+        Algorithm::GreedyStringTiling,
+        Algorithm::Ast,
+    ] {
+        let mut settings = DatabaseSettings::default();
+        settings.comparison.algorithm = algorithm;
+        let pair = checked_pair(
+            settings,
+            &ComparatorRegistry::default(),
+            submission("Ivanov", &[("lab.sql", STUDENTS_SQL)]),
+            submission("Petrenko", &[("main.c", SUM_C)]),
+        )?;
+
+        assert_eq!(pair.algorithm, COMPRESSION);
+        assert_eq!(pair.verdict, Verdict::Clear);
+    }
+    Ok(())
+}
+
+#[test]
+fn custom_comparator_falls_back_to_compression() -> Result<(), Box<dyn Error>> {
+    let mut registry = ComparatorRegistry::default();
+    registry.register(Everything);
+    let settings = || {
+        let mut settings = DatabaseSettings::default();
+        settings.comparison.algorithm = Algorithm::Custom("Everything".to_string());
+        settings
+    };
+
+    let pair = checked_pair(
+        settings(),
+        &registry,
+        submission("Ivanov", &[("lab.sql", STUDENTS_SQL)]),
+        submission("Petrenko", &[("lab.sql", ORDERS_SQL)]),
+    )?;
+    assert_eq!(pair.algorithm, COMPRESSION);
+    assert_eq!(pair.verdict, Verdict::Clear);
+
+    let pair = checked_pair(
+        settings(),
+        &registry,
+        submission("Ivanov", &[("main.c", SUM_C)]),
+        submission("Petrenko", &[("main.c", SUM_C)]),
+    )?;
+    assert_eq!(pair.algorithm, "Everything");
+    Ok(())
+}
+
+#[test]
+fn file_pairs_record_their_comparators() -> Result<(), Box<dyn Error>> {
+    let mut settings = DatabaseSettings::default();
+    settings.comparison.aggregation.mode = AggregationMode::File;
+    let files = [("lab.sql", STUDENTS_SQL), ("main.c", SUM_C)];
+
+    let pair = checked_pair(
+        settings,
+        &ComparatorRegistry::default(),
+        submission("Ivanov", &files),
+        submission("Petrenko", &files),
+    )?;
+    assert_eq!(pair.algorithm, format!("{COMPRESSION}, Winnowing"));
+    assert_eq!(pair.verdict, Verdict::High);
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use engine::Database;
+use engine::models::database::{META_FILE_NAME, SETTINGS_FILE_NAME};
+use engine::models::migration::FORMAT_VERSION;
+use serde_json::{Value, json};
+use std::error::Error;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+const CODE: &str = "int main() { return 0; }";
+
+/// Archive as written before the format was versioned: metadata is encoded in the paths.
+fn baseline_archive(
+    directory: &Path, name: &str, file_name_pattern: Value, files: &[&str],
+) -> Result<PathBuf, Box<dyn Error>> {
+    let path = directory.join(format!("{name}.xai"));
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+    let options = SimpleFileOptions::default();
+
+    let meta = json!({
+        "id": "00000000-0000-0000-0000-000000000000",
+        "name": name,
+        "description": null,
+    });
+    zip.start_file(META_FILE_NAME, options)?;
+    zip.write_all(meta.to_string().as_bytes())?;
+
+    let settings = json!({
+        "file_name_pattern": file_name_pattern,
+        "ignore_list": {
+            "accepted_extensions": ["c"],
+            "ignored_directories": [],
+        },
+    });
+    zip.start_file(SETTINGS_FILE_NAME, options)?;
+    zip.write_all(settings.to_string().as_bytes())?;
+
+    for file in files {
+        zip.start_file(*file, options)?;
+        zip.write_all(CODE.as_bytes())?;
+    }
+    zip.finish()?;
+
+    Ok(path)
+}
+
+fn stored_format_version(path: &Path) -> Result<Option<u64>, Box<dyn Error>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+    let mut meta = String::new();
+    archive.by_name(META_FILE_NAME)?.read_to_string(&mut meta)?;
+
+    let meta: Value = serde_json::from_str(&meta)?;
+    Ok(meta.get("format_version").and_then(Value::as_u64))
+}
+
+/// Loads the baseline archive and saves it again, checking the migrated submissions.
+fn assert_migrated(
+    path: &Path, expected: &[(&str, Option<&str>)],
+) -> Result<(), Box<dyn Error>> {
+    let mut database = Database::load(path)?;
+    assert!(database.is_dirty);
+    assert_eq!(database.meta.format_version, FORMAT_VERSION);
+
+    let mut metadata: Vec<(&str, Option<&str>)> = database
+        .submissions
+        .iter()
+        .map(|submission| {
+            (
+                submission.metadata.student_name.as_str(),
+                submission.metadata.assignment_title.as_deref(),
+            )
+        })
+        .collect();
+    metadata.sort();
+    assert_eq!(metadata, expected);
+    for submission in &database.submissions {
+        assert_eq!(submission.version, 1);
+        assert!(submission.history.is_empty());
+        assert_eq!(submission.files.len(), 1);
+        assert!(submission.files.iter().all(|file| file.content == CODE));
+    }
+
+    database.save()?;
+    assert!(!database.is_dirty);
+    assert_eq!(
+        stored_format_version(path)?,
+        Some(u64::from(FORMAT_VERSION))
+    );
+
+    let reloaded = Database::load(path)?;
+    assert!(!reloaded.is_dirty);
+    let ids = |database: &Database| {
+        let mut ids: Vec<String> = database
+            .submissions
+            .iter()
+            .map(|submission| submission.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    };
+    assert_eq!(ids(&reloaded), ids(&database));
+
+    Ok(())
+}
+
+#[test]
+fn baseline_student_task_archive_is_migrated() -> Result<(), Box<dyn Error>> {
+    let directory = tempfile::tempdir()?;
+    let path = baseline_archive(
+        directory.path(),
+        "student-task",
+        json!({ "StudentTask": { "separator": "_" } }),
+        &[
+            "submissions/Ivanov/Lab1/main.c",
+            "submissions/Ivanov/Lab2/main.c",
+            "submissions/Petrenko/Lab1/src/main.c",
+        ],
+    )?;
+
+    assert_migrated(
+        &path,
+        &[
+            ("Ivanov", Some("Lab1")),
+            ("Ivanov", Some("Lab2")),
+            ("Petrenko", Some("Lab1")),
+        ],
+    )
+}
+
+#[test]
+fn baseline_student_only_archive_is_migrated() -> Result<(), Box<dyn Error>> {
+    let directory = tempfile::tempdir()?;
+    let path = baseline_archive(
+        directory.path(),
+        "student-only",
+        json!("StudentOnly"),
+        &[
+            "submissions/Ivanov/main.c",
+            "submissions/Petrenko/src/main.c",
+        ],
+    )?;
+
+    assert_migrated(&path, &[("Ivanov", None), ("Petrenko", None)])
+}
+
+#[test]
+fn newer_format_is_rejected() -> Result<(), Box<dyn Error>> {
+    let directory = tempfile::tempdir()?;
+    let path = baseline_archive(directory.path(), "newer", json!("StudentOnly"), &[])?;
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path)?)?;
+    let settings = {
+        let mut settings = String::new();
+        archive
+            .by_name(SETTINGS_FILE_NAME)?
+            .read_to_string(&mut settings)?;
+        settings
+    };
+
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+    let meta = json!({
+        "id": "00000000-0000-0000-0000-000000000000",
+        "name": "newer",
+        "description": null,
+        "format_version": FORMAT_VERSION + 1,
+    });
+    zip.start_file(META_FILE_NAME, SimpleFileOptions::default())?;
+    zip.write_all(meta.to_string().as_bytes())?;
+    zip.start_file(SETTINGS_FILE_NAME, SimpleFileOptions::default())?;
+    zip.write_all(settings.as_bytes())?;
+    zip.finish()?;
+
+    assert!(Database::load(&path).is_err());
+    Ok(())
+}
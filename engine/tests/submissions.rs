@@ -0,0 +1,153 @@
+use engine::Database;
+use engine::compare::control::CheckControl;
+use engine::compare::registry::ComparatorRegistry;
+use engine::models::collaboration::Team;
+use engine::models::database::DatabaseSettings;
+use engine::models::result::PairResult;
+use engine::models::review::ReviewDecision;
+use engine::models::submission::{CodeFile, Submission, SubmissionMetadata};
+use engine::models::verdict::{Thresholds, Verdict};
+use std::error::Error;
+use std::path::Path;
+
+const CODE: &str = "int main() {
+    int sum = 0;
+    for (int i = 0; i < 10; i++) {
+        sum += i * i;
+    }
+    printf(\"%d\", sum);
+    return sum;
+}";
+
+fn submission(student_name: &str) -> Submission {
+    submission_with(student_name, CODE)
+}
+
+fn submission_with(student_name: &str, code: &str) -> Submission {
+    Submission::new(
+        SubmissionMetadata {
+            student_name: student_name.to_string(),
+            assignment_title: Some("Lab1".to_string()),
+        },
+        vec![CodeFile {
+            relative_path: "main.c".to_string(),
+            content: code.to_string(),
+            extension: "c".to_string(),
+        }],
+    )
+}
+
+fn checked_database(directory: &Path) -> Result<Database, Box<dyn Error>> {
+    let mut database = Database::new(
+        "Course".to_string(),
+        None,
+        DatabaseSettings::default(),
+        directory.join("course.xai"),
+    );
+    database.submissions = vec![submission("Ivanov"), submission("Petrenko")];
+    database.check(
+        &[],
+        &ComparatorRegistry::default(),
+        &CheckControl::default(),
+    )?;
+    Ok(database)
+}
+
+fn latest_pair(database: &Database) -> Result<PairResult, Box<dyn Error>> {
+    database
+        .latest_result()
+        .and_then(|result| result.pairs.first())
+        .cloned()
+        .ok_or_else(|| "check result has no pairs".into())
+}
+
+#[test]
+fn review_applies_after_rename() -> Result<(), Box<dyn Error>> {
+    let directory = tempfile::tempdir()?;
+    let mut database = checked_database(directory.path())?;
+    let pair = latest_pair(&database)?;
+    database.review(
+        &pair,
+        "Reviewer".to_string(),
+        ReviewDecision::Confirmed,
+        String::new(),
+    );
+
+    let id = pair.first_id.clone();
+    database.rename_student(&id, "Ivanova".to_string())?;
+
+    let pair = latest_pair(&database)?;
+    assert!(
+        pair.first.student_name == "Ivanova" || pair.second.student_name == "Ivanova"
+    );
+    assert_eq!(
+        database.latest_review(&pair).map(|review| review.decision),
+        Some(ReviewDecision::Confirmed)
+    );
+
+    database.check(
+        &[],
+        &ComparatorRegistry::default(),
+        &CheckControl::default(),
+    )?;
+    let pair = latest_pair(&database)?;
+    assert_eq!(
+        database.latest_review(&pair).map(|review| review.decision),
+        Some(ReviewDecision::Confirmed)
+    );
+    Ok(())
+}
+
+#[test]
+fn team_applies_after_rename() -> Result<(), Box<dyn Error>> {
+    let directory = tempfile::tempdir()?;
+    let mut database = checked_database(directory.path())?;
+    database.add_team(Team::new(
+        None,
+        vec!["Ivanov".to_string(), "Petrenko".to_string()],
+    ));
+    let pair = latest_pair(&database)?;
+    assert!(pair.is_expected);
+
+    database.rename_student(&pair.first_id, "Ivanova".to_string())?;
+    assert!(latest_pair(&database)?.is_expected);
+    Ok(())
+}
+
+#[test]
+fn discarded_regions_are_restored_when_flagged() -> Result<(), Box<dyn Error>> {
+    let directory = tempfile::tempdir()?;
+    let mut settings = DatabaseSettings::default();
+    settings.retention.keep_clear_regions = false;
+    settings.thresholds = Thresholds::new(1.0, 1.0)?;
+    let mut database = Database::new(
+        "Course".to_string(),
+        None,
+        settings,
+        directory.path().join("course.xai"),
+    );
+    let changed = format!("{CODE}\n\nint unused(int value) {{ return value * 2 + 1; }}");
+    database.submissions =
+        vec![submission("Ivanov"), submission_with("Petrenko", &changed)];
+
+    let check = |database: &mut Database| {
+        database.check(
+            &[],
+            &ComparatorRegistry::default(),
+            &CheckControl::default(),
+        )
+    };
+    check(&mut database)?;
+    let pair = latest_pair(&database)?;
+    assert_eq!(pair.verdict, Verdict::Clear);
+    assert!(pair.regions_discarded);
+    assert!(pair.regions.is_empty());
+
+    database.set_thresholds(Thresholds::new(0.0, 0.0)?);
+    check(&mut database)?;
+    let pair = latest_pair(&database)?;
+    assert_eq!(pair.verdict, Verdict::High);
+    assert!(!pair.regions_discarded);
+    assert!(!pair.regions.is_empty());
+    Ok(())
+}